@@ -1,9 +1,19 @@
-use crate::chunk::{Chunk, ParseChunkError};
-use crate::chunk_type::{ChunkType, ParseChunkTypeError};
-use crate::png::{ParsePngError, Png};
-use clap::{Args, Parser, Subcommand};
+use crate::Result;
+use crate::chunk::{Chunk, PayloadHeader};
+use crate::chunk_type::ChunkType;
+use crate::conversions::{
+    COMPRESSED_MARKER, bytes_to_base64, bytes_to_hex, compress_marked, decompress_marked,
+    hex_to_bytes,
+};
+use crate::crypto::{decrypt_message, encrypt_message, is_encrypted};
+use crate::png::Png;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 #[derive(Parser, Debug)]
@@ -11,73 +21,585 @@ use std::str::FromStr;
 pub struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// When to color the output
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+    /// Prints the output of list and count as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    /// Prints only the output itself, without decorative prefixes
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also prints progress lines to stderr as the command runs
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// Lets decode, export and list read files whose Chunks have crc mismatches
+    #[arg(long, global = true)]
+    ignore_crc: bool,
+    /// The largest input file, in bytes, that will be read into memory
+    #[arg(long, global = true, value_name = "BYTES", default_value_t = DEFAULT_MAX_INPUT_SIZE)]
+    max_size: u64,
+}
+
+/// The largest input read when --max-size isn't given: 256 MiB
+const DEFAULT_MAX_INPUT_SIZE: u64 = 256 * 1024 * 1024;
+
+/// How much a command reports about what it does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Verbosity {
+    /// Only the output itself
+    Quiet,
+    /// The output with its usual prefixes
+    Normal,
+    /// The output plus progress lines on stderr
+    Verbose,
+}
+
+/// Functions that report progress under a Verbosity
+impl Verbosity {
+    /// Prints the given progress line to stderr if this Verbosity is Verbose
+    fn progress(&self, line: &str) {
+        if *self == Verbosity::Verbose {
+            eprintln!("{}", line);
+        }
+    }
+
+    /// Prints the given note about what a command chose to stdout unless this
+    /// Verbosity is Quiet
+    fn note(&self, line: &str) {
+        if *self != Verbosity::Quiet {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Decides whether output should be colored
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is unset or empty
+    Auto,
+    /// Always color, even when the output is piped
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Functions that resolve a ColorChoice into colored output
+impl ColorChoice {
+    /// Determines if output should be colored under this ColorChoice
+    fn enabled(&self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                // per no-color.org, only a non-empty NO_COLOR turns color off
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+                !no_color && std::io::stdout().is_terminal()
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    /// Wraps the given text in the given ANSI color code if this ColorChoice is enabled
+    fn paint(&self, text: &str, ansi_code: u8) -> String {
+        if self.enabled() {
+            format!("\x1b[{ansi_code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Encodes the file by adding a Chunk with the given ChunkType and message
+    /// Encodes the file by adding a Chunk with the given ChunkType and message right before IEND
     Encode(EncodeParams),
     /// Decodes the first Chunk with the given ChunkType in the file
     Decode(DecodeParams),
     /// Removes the first Chunk with the given ChunkType in the file
     Remove(RemoveParams),
-    /// Prints the hidden messages stored within the hidden file
+    /// Prints a summary of the file with a preview of the message in each Chunk
     Print(PrintParams),
+    /// Lists the index, ChunkType, length, and crc of every Chunk in the file
+    List(ListParams),
+    /// Writes the full bytes of the first Chunk with the given ChunkType to a file
+    DumpChunk(DumpChunkParams),
+    /// Inserts a Chunk read from a dumped chunk file right before the IEND Chunk
+    Inject(InjectParams),
+    /// Counts how many Chunks of each ChunkType are in the file
+    Count(CountParams),
+    /// Changes the ChunkType of every ancillary Chunk in the file to the given private ChunkType
+    RetypeAll(RetypeAllParams),
+    /// Reports whether the file likely contains hidden data
+    Detect(DetectParams),
+    /// Replaces the message of the first Chunk with the given ChunkType, keeping its position
+    Replace(ReplaceParams),
+    /// Checks the crc of every Chunk and that the file starts with IHDR and ends with IEND
+    Verify(VerifyParams),
+    /// Writes only the raw data of the first Chunk with the given ChunkType to a file
+    Export(ExportParams),
+    /// Moves the Chunk at one index to another, shifting the Chunks in between
+    Move(MoveParams),
+    /// Reports how many bytes of the file are Chunk data versus overhead
+    Stats(StatsParams),
+    /// Changes the ChunkType of the Chunk at an index, keeping its data and position
+    Rename(RenameParams),
+    /// Lists every Chunk whose data contains the given text or bytes
+    Find(FindParams),
+    /// Lists the Chunks added, removed, or changed between two files
+    Diff(DiffParams),
+    /// Removes every ancillary Chunk, leaving only the critical Chunks
+    Scrub(ScrubParams),
+    /// Prints a tab completion script for the given shell
+    #[command(hide = true)]
+    Completions(CompletionsParams),
 }
 
 impl Cli {
     /// Processes this command
-    pub fn process_command(&self) {
+    pub fn process_command(&self) -> Result<()> {
+        let verbosity = self.verbosity();
         match &self.command {
             Commands::Encode(params) => {
-                params.process_command();
+                if let Some(summary) = params.process_command(verbosity, self.max_size)? {
+                    println!("{}", summary);
+                }
             }
             Commands::Decode(params) => {
-                println!("Your Decoded Picture:\n{}", params.process_command());
+                if let Some(message) =
+                    params.process_command(verbosity, self.ignore_crc, self.max_size)?
+                {
+                    println!("{}", decoded_output(&message, verbosity));
+                }
             }
             Commands::Remove(params) => {
-                params.process_command();
+                if let Some(summary) = params.process_command(verbosity, self.max_size)? {
+                    println!("{}", summary);
+                }
             }
             Commands::Print(params) => {
-                println!("{}", params.process_command());
+                if let Some(listing) = params.process_command(self.max_size)? {
+                    println!("{}", listing);
+                }
+            }
+            Commands::List(params) => {
+                println!(
+                    "{}",
+                    params.process_command(
+                        self.json,
+                        self.color,
+                        self.ignore_crc,
+                        self.max_size
+                    )?
+                );
+            }
+            Commands::DumpChunk(params) => {
+                params.process_command(self.max_size)?;
+            }
+            Commands::Inject(params) => {
+                params.process_command(verbosity, self.max_size)?;
+            }
+            Commands::Count(params) => {
+                println!("{}", params.process_command(self.json, self.max_size)?);
+            }
+            Commands::RetypeAll(params) => {
+                if let Some(summary) = params.process_command(verbosity, self.max_size)? {
+                    println!("{}", summary);
+                }
+            }
+            Commands::Detect(params) => {
+                println!("{}", params.process_command(self.max_size)?);
+            }
+            Commands::Replace(params) => {
+                if let Some(summary) = params.process_command(verbosity, self.max_size)? {
+                    println!("{}", summary);
+                }
+            }
+            Commands::Verify(params) => {
+                println!("{}", params.process_command(self.max_size)?);
+            }
+            Commands::Export(params) => {
+                params.process_command(self.ignore_crc, self.max_size)?;
+            }
+            Commands::Move(params) => {
+                params.process_command(verbosity, self.max_size)?;
+            }
+            Commands::Stats(params) => {
+                println!("{}", params.process_command(self.max_size)?);
+            }
+            Commands::Rename(params) => {
+                params.process_command(verbosity, self.max_size)?;
             }
+            Commands::Find(params) => {
+                println!("{}", params.process_command(self.max_size)?);
+            }
+            Commands::Diff(params) => {
+                println!("{}", params.process_command(self.max_size)?);
+            }
+            Commands::Scrub(params) => {
+                if let Some(summary) = params.process_command(verbosity, self.max_size)? {
+                    println!("{}", summary);
+                }
+            }
+            Commands::Completions(params) => {
+                print!("{}", params.process_command()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the Verbosity chosen by the --quiet and --verbose flags
+    fn verbosity(&self) -> Verbosity {
+        match (self.quiet, self.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, true) => Verbosity::Verbose,
+            (false, false) => Verbosity::Normal,
+        }
+    }
+}
+
+/// Formats a decoded message for printing: as is when quiet so it can be piped,
+/// otherwise after a heading
+fn decoded_output(message: &str, verbosity: Verbosity) -> String {
+    match verbosity {
+        Verbosity::Quiet => message.to_string(),
+        Verbosity::Normal | Verbosity::Verbose => format!("Your Decoded Picture:\n{}", message),
+    }
+}
+
+/// Reads all bytes from the file at the given path, or from stdin if the path is "-".
+/// If the input is larger than max_size bytes, return an error without reading it into memory
+fn read_input(path: &Path, max_size: u64) -> Result<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut bytes = Vec::<u8>::new();
+        // reads one byte past the limit so too large an input can be told apart
+        std::io::stdin()
+            .take(max_size.saturating_add(1))
+            .read_to_end(&mut bytes)
+            .map_err(|err| format!("could not read stdin: {}", err))?;
+        if bytes.len() as u64 > max_size {
+            return Err(format!(
+                "input too large (more than {} bytes); pass --max-size to override",
+                max_size
+            )
+            .into());
+        }
+        return Ok(bytes);
+    }
+
+    let size = fs::metadata(path)
+        .map_err(|err| format!("could not read file '{}': {}", path.display(), err))?
+        .len();
+    if size > max_size {
+        return Err(format!(
+            "file too large ({} bytes); pass --max-size to override",
+            size
+        )
+        .into());
+    }
+
+    Ok(fs::read(path)
+        .map_err(|err| format!("could not read file '{}': {}", path.display(), err))?)
+}
+
+/// Reads the file at the given path, or stdin if the path is "-", and parses it into a Png
+fn read_png(path: &Path, max_size: u64) -> Result<Png> {
+    read_png_with(path, false, max_size)
+}
+
+/// Reads the file at the given path like read_png. If ignore_crc is set, Chunks are still
+/// split by their length fields but one whose crc doesn't match is kept instead of rejected
+fn read_png_with(path: &Path, ignore_crc: bool, max_size: u64) -> Result<Png> {
+    let bytes = read_input(path, max_size)?;
+    if ignore_crc {
+        return Ok(Png::try_from_lenient(&bytes)?);
+    }
+
+    Ok(Png::try_from(bytes)?)
+}
+
+/// Finds the first Chunk with the given ChunkType in the given Png, or the last one if
+/// last is set. If there is no such Chunk, return an error
+fn find_chunk<'a>(png: &'a Png, chunk_type: &str, last: bool) -> Result<&'a Chunk> {
+    let chunk = match last {
+        true => png.last_chunk_by_type(chunk_type),
+        false => png.chunk_by_type(chunk_type),
+    };
+
+    Ok(chunk.ok_or_else(|| format!("no chunk of type '{}' found", chunk_type))?)
+}
+
+/// Returns where a command that changes the input file should write its result: the
+/// output file if given, otherwise the input file itself if overwriting it is allowed.
+/// If the input is stdin or may not be overwritten and there is no output file, return an error
+fn output_path<'a>(
+    path: &'a Path,
+    output_file: Option<&'a Path>,
+    overwrite: bool,
+) -> Result<&'a Path> {
+    match output_file {
+        Some(out_path) => Ok(out_path),
+        None if path == Path::new("-") => {
+            Err("reading from stdin requires an output file to write to".into())
         }
+        None if !overwrite => Err("refusing to overwrite input; pass --force or --output".into()),
+        None => Ok(path),
+    }
+}
+
+/// Writes the given bytes to the file at the given path by writing a temporary file in
+/// the same directory and renaming it over the path, so an interrupted write never
+/// leaves the file half written
+fn write_output(path: &Path, bytes: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("could not write file '{}': not a file path", path.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".pngme-{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    // the data is synced to disk before the rename so a crash can't leave the rename
+    // in place without it, and an existing file keeps its permissions after being replaced
+    let written = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| match fs::metadata(path) {
+            Ok(metadata) => fs::set_permissions(&temp_path, metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("could not write file '{}': {}", path.display(), err).into());
+    }
+
+    Ok(())
+}
+
+/// Writes the given Png to the file at the given path
+fn write_png(path: &Path, png: &Png) -> Result<()> {
+    write_output(path, &png.as_bytes())
+}
+
+/// Copies the file at the given path to the same path plus the given suffix.
+/// If the backup file already exists, return an error instead of replacing it
+fn backup_file(path: &Path, suffix: &str) -> Result<()> {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(suffix);
+    let backup_path = PathBuf::from(backup_path);
+
+    if backup_path.exists() {
+        return Err(format!("backup file '{}' already exists", backup_path.display()).into());
     }
+    fs::copy(path, &backup_path).map_err(|err| {
+        format!(
+            "could not back up '{}' to '{}': {}",
+            path.display(),
+            backup_path.display(),
+            err
+        )
+    })?;
+
+    Ok(())
 }
 
-// #[derive(Debug)]
-// enum PngmeError {
-//     ParseChunkTypeError,
-//     ParseChunkError,
-//     ParsePngError,
-// }
+/// Finishes a command that changes a Png. On a dry run, given the action that was taken,
+/// returns a summary of it and how the file size would change instead of writing the Png.
+/// Otherwise, given a backup suffix, backs up the file at the given path before writing
+fn finish_change(
+    path: &Path,
+    png: &Png,
+    old_len: usize,
+    dry_run_action: Option<String>,
+    backup_suffix: Option<&str>,
+) -> Result<Option<String>> {
+    let Some(action) = dry_run_action else {
+        if let Some(suffix) = backup_suffix
+            && path.exists()
+        {
+            backup_file(path, suffix)?;
+        }
+        write_png(path, png)?;
+        return Ok(None);
+    };
+
+    let new_len = png.byte_len();
+    let size_change = match new_len.cmp(&old_len) {
+        std::cmp::Ordering::Greater => format!("grow from {} to {} bytes", old_len, new_len),
+        std::cmp::Ordering::Less => format!("shrink from {} to {} bytes", old_len, new_len),
+        std::cmp::Ordering::Equal => format!("stay at {} bytes", old_len),
+    };
+    Ok(Some(format!(
+        "would {}; file would {}",
+        action, size_change
+    )))
+}
 
 /// Params taht can be passed into the encode command
 #[derive(Args, Debug)]
 struct EncodeParams {
     path: PathBuf,
-    chunk_type: String,
-    message: String,
+    /// Left out with --auto-type
+    #[arg(required_unless_present = "auto_type")]
+    chunk_type: Option<String>,
+    #[arg(required_unless_present = "message_file")]
+    message: Option<String>,
+    /// Writes the result to this file instead of the input file. The same as -o/--output,
+    /// which it can't be combined with
     output_file: Option<PathBuf>,
+    /// Writes the result to this file instead of the input file. Use it when there is no
+    /// message positional, such as with --message-file. Can't be combined with OUTPUT_FILE
+    #[arg(short, long = "output", conflicts_with = "output_file")]
+    output: Option<PathBuf>,
+    /// Stores the bytes of this file as the message instead, or stdin if it is "-"
+    #[arg(long, conflicts_with = "message")]
+    message_file: Option<PathBuf>,
+    /// Uses a safe private chunk type instead of a given one. The message must then come
+    /// from --message-file
+    #[arg(long, conflicts_with = "chunk_type", requires = "message_file")]
+    auto_type: bool,
+    /// Stores the message and every --field as length-prefixed fields
+    #[arg(long)]
+    frame: bool,
+    /// An extra field to store after the message. Requires --frame
+    #[arg(long, requires = "frame")]
+    field: Vec<String>,
+    /// Compresses the message with zlib before storing it. Decode inflates it again
+    #[arg(long)]
+    compress: bool,
+    /// Encrypts the message with this password. Decode needs the same password
+    #[arg(long)]
+    password: Option<String>,
+    /// Puts a header in front of the data recording whether it was compressed or
+    /// encrypted, so decode reverses both without being told
+    #[arg(long)]
+    header: bool,
+    /// Appends this many identical Chunks, up to EncodeParams::MAX_REPEAT
+    #[arg(long, default_value_t = 1)]
+    repeat: usize,
+    /// Inserts the Chunk at this index instead of right before IEND
+    #[arg(long, value_name = "INDEX")]
+    at: Option<usize>,
+    /// Prints what would change without writing any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+    /// Copies the file about to be overwritten to its path plus this suffix first
+    #[arg(long, value_name = "SUFFIX", num_args = 0..=1, require_equals = true, default_missing_value = ".bak")]
+    backup: Option<String>,
+    /// Reads the file even if crcs don't match and recalculates every crc before writing
+    #[arg(long)]
+    fix_crc: bool,
 }
 
 /// Functions that use the Encode paramters to do something
 impl EncodeParams {
-    /// Processes and performs the encode action using the given paramters
-    fn process_command(&self) {
-        let given_png_as_bytes = fs::read(&self.path).unwrap();
-        let mut png = Png::try_from(given_png_as_bytes.as_slice()).unwrap();
-        let chunk = Chunk::new(
-            ChunkType::from_str(&self.chunk_type).unwrap(),
-            self.message.as_bytes().to_vec(),
-        );
+    /// The most Chunks a single encode may append with --repeat
+    const MAX_REPEAT: usize = 10000;
 
-        png.append_chunk(chunk);
+    /// Processes and performs the encode action using the given paramters.
+    /// Returns a summary of the change instead of writing it on a dry run
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<Option<String>> {
+        if !(1..=EncodeParams::MAX_REPEAT).contains(&self.repeat) {
+            return Err(format!(
+                "--repeat must be between 1 and {}",
+                EncodeParams::MAX_REPEAT
+            )
+            .into());
+        }
+        // clap rejects giving both, so at most one of them is set
+        let out_path = output_path(
+            &self.path,
+            self.output.as_deref().or(self.output_file.as_deref()),
+            self.force || self.dry_run,
+        )?;
+        let mut png = read_png_with(&self.path, self.fix_crc, max_size)?;
+        let old_len = png.byte_len();
+        verbosity.progress(&format!("read {} bytes", old_len));
+        let chunk_type = match (&self.chunk_type, self.auto_type) {
+            (None, true) => {
+                let chunk_type = ChunkType::safe_private();
+                verbosity.note(&format!("Encoding with chunk type {}", chunk_type));
+                chunk_type
+            }
+            (Some(chunk_type), false) => ChunkType::from_str(chunk_type)?,
+            _ => return Err("give either a chunk type or --auto-type".into()),
+        };
+        let message = match (&self.message, &self.message_file) {
+            (_, Some(message_file)) => read_input(message_file, max_size)?,
+            (Some(message), None) => message.as_bytes().to_vec(),
+            (None, None) => return Err("a message or --message-file is required".into()),
+        };
+        let mut data = if self.frame {
+            let mut fields = vec![message.as_slice()];
+            fields.extend(self.field.iter().map(|field| field.as_bytes()));
+            Chunk::new_framed(chunk_type.clone(), &fields)
+                .data()
+                .to_vec()
+        } else {
+            message
+        };
+        if self.compress {
+            data = compress_marked(&data);
+        }
+        if let Some(password) = &self.password {
+            data = encrypt_message(&data, password);
+        }
+        if self.header {
+            let header = PayloadHeader {
+                compressed: self.compress,
+                encrypted: self.password.is_some(),
+            };
+            data = [header.to_bytes().as_slice(), &data].concat();
+        }
+        let chunk = Chunk::try_new(chunk_type, data)?;
 
-        match &self.output_file {
-            Some(out_path) => fs::write(out_path, png.as_bytes()).unwrap(),
-            None => fs::write(&self.path, png.as_bytes()).unwrap(),
+        let mut action = match self.repeat {
+            1 => format!(
+                "append chunk '{}' ({} bytes)",
+                chunk.chunk_type(),
+                chunk.length()
+            ),
+            repeat => format!(
+                "append {} chunks '{}' ({} bytes each)",
+                repeat,
+                chunk.chunk_type(),
+                chunk.length()
+            ),
+        };
+        if let Some(index) = self.at {
+            action.push_str(&format!(" at index {}", index));
+        }
+
+        // EFFECT: Appends the same Chunk once for every repeat
+        for _ in 0..self.repeat {
+            match self.at {
+                Some(index) => png.insert_chunk_at(index, chunk.clone())?,
+                None => png.insert_before_end(chunk.clone())?,
+            }
+            verbosity.progress(&format!("appended chunk '{}'", chunk.chunk_type()));
+        }
+        if self.fix_crc {
+            png.recompute_crcs();
+        }
+
+        let summary = finish_change(
+            out_path,
+            &png,
+            old_len,
+            self.dry_run.then_some(action),
+            self.backup.as_deref(),
+        )?;
+        if summary.is_none() {
+            verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
         }
+
+        Ok(summary)
     }
 }
 
@@ -85,37 +607,347 @@ impl EncodeParams {
 #[derive(Args, Debug)]
 struct DecodeParams {
     path: PathBuf,
-    chunk_type: String,
+    #[arg(required_unless_present = "index")]
+    chunk_type: Option<String>,
+    /// Decodes the Chunk at this index instead of the first one with a ChunkType
+    #[arg(long, conflicts_with = "chunk_type")]
+    index: Option<usize>,
+    /// Decodes the last Chunk with the ChunkType instead of the first
+    #[arg(long, conflicts_with = "index")]
+    last: bool,
+    /// How to print the decoded chunk data
+    #[arg(long, value_enum, default_value_t = Encoding::Utf8)]
+    encoding: Encoding,
+    /// Writes the raw chunk data to this file instead of printing it
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
+    /// The password the message was encrypted with
+    #[arg(long)]
+    password: Option<String>,
+    /// Fails instead of printing or writing data that isn't valid UTF-8 text
+    #[arg(long, conflicts_with = "encoding")]
+    text_only: bool,
+}
+
+/// How the decode command prints chunk data
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    /// The data as UTF-8 text
+    Utf8,
+    /// The data as lowercase hex digits
+    Hex,
+    /// The data as standard base64
+    Base64,
+}
+
+/// Functions that format chunk data under an Encoding
+impl Encoding {
+    /// Formats the given data under this Encoding.
+    /// If the data can't be shown under this Encoding, return an error
+    fn format(&self, data: &[u8]) -> Result<String> {
+        match self {
+            Encoding::Utf8 => Ok(String::from_utf8(data.to_vec())?),
+            Encoding::Hex => Ok(bytes_to_hex(data)),
+            Encoding::Base64 => Ok(bytes_to_base64(data)),
+        }
+    }
 }
 
 /// Functions that use the Decode parameters to do something
 impl DecodeParams {
-    /// Processes and performs the decode action using the given parameters
-    fn process_command(&self) -> String {
-        let png_as_bytes = fs::read(&self.path).unwrap();
-        let png = Png::try_from(png_as_bytes.as_slice()).unwrap();
-        let decoded_chunk = png.chunk_by_type(&self.chunk_type).unwrap();
+    /// Processes and performs the decode action using the given parameters.
+    /// Returns the decoded message unless it was written to the output file.
+    /// If ignore_crc is set, Chunks with crc mismatches are read anyway
+    fn process_command(
+        &self,
+        verbosity: Verbosity,
+        ignore_crc: bool,
+        max_size: u64,
+    ) -> Result<Option<String>> {
+        let png = read_png_with(&self.path, ignore_crc, max_size)?;
+        verbosity.progress(&format!("read {} bytes", png.byte_len()));
+        let decoded_chunk = match (self.index, &self.chunk_type) {
+            (Some(index), _) => png.chunk_by_index(index).ok_or_else(|| {
+                format!(
+                    "no chunk at index {}: the file has {} chunks",
+                    index,
+                    png.len()
+                )
+            })?,
+            (None, Some(chunk_type)) => find_chunk(&png, chunk_type, self.last)?,
+            (None, None) => return Err("a chunk type or --index is required".into()),
+        };
+        verbosity.progress(&format!(
+            "found chunk '{}' ({} bytes)",
+            decoded_chunk.chunk_type(),
+            decoded_chunk.length()
+        ));
+
+        let data = unwrap_payload(decoded_chunk, self.password.as_deref())?;
+        if self.text_only && std::str::from_utf8(&data).is_err() {
+            return Err("chunk data is not valid UTF-8".into());
+        }
+
+        match &self.output_file {
+            Some(out_path) => {
+                write_output(out_path, &data)?;
+                verbosity.progress(&format!("wrote {} bytes", data.len()));
+                Ok(None)
+            }
+            None => Ok(Some(self.encoding.format(&data)?)),
+        }
+    }
+}
+
+/// Reverses the encryption and compression of the data in the given Chunk as its
+/// PayloadHeader records. Data without a header is checked for the markers each
+/// transformation leaves instead. If the header disagrees with the markers, or the data
+/// is encrypted and no password is given: return an error
+fn unwrap_payload(chunk: &Chunk, password: Option<&str>) -> Result<Vec<u8>> {
+    let (header, body) = chunk.parse_payload_header()?;
+    let check_header =
+        |transform: &str, applied: bool, flagged: fn(PayloadHeader) -> bool| match header {
+            Some(header) if flagged(header) != applied => Err(format!(
+                "payload header disagrees with the chunk data about {}",
+                transform
+            )),
+            _ => Ok(()),
+        };
+
+    let encrypted = is_encrypted(body);
+    check_header("encryption", encrypted, |header| header.encrypted)?;
+    let data = match (encrypted, password) {
+        (true, Some(password)) => decrypt_message(body, password)?,
+        (true, None) => {
+            return Err("chunk data is encrypted: pass --password to decode it".into());
+        }
+        (false, _) => body.to_vec(),
+    };
 
-        decoded_chunk.data_as_string().unwrap()
+    // compression happens before encryption, so its marker is only visible now
+    let compressed = data.starts_with(&COMPRESSED_MARKER);
+    check_header("compression", compressed, |header| header.compressed)?;
+    if !compressed {
+        return Ok(data);
     }
+
+    Ok(decompress_marked(&data)
+        .map_err(|err| format!("could not decompress chunk data: {}", err))?)
 }
 
 /// Holds the paramters for the Remove command
 #[derive(Args, Debug)]
 struct RemoveParams {
-    path: PathBuf,
-    chunk_type: String,
+    /// The files to remove from, followed by the ChunkType to remove unless --index is given
+    #[arg(required = true, value_name = "PATH")]
+    paths: Vec<PathBuf>,
+    /// Removes the Chunk at this index instead of the first one with a ChunkType
+    #[arg(long)]
+    index: Option<usize>,
+    /// Removes every Chunk with the ChunkType instead of only the first
+    #[arg(long, conflicts_with = "index")]
+    all: bool,
+    /// Writes the result to this file instead of the input file. Only allowed with a
+    /// single file
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
+    /// Prints what would change without writing any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+    /// Copies the file about to be overwritten to its path plus this suffix first
+    #[arg(long, value_name = "SUFFIX", num_args = 0..=1, require_equals = true, default_missing_value = ".bak")]
+    backup: Option<String>,
+    /// Reads the file even if crcs don't match and recalculates every crc before writing
+    #[arg(long)]
+    fix_crc: bool,
 }
 
 /// Functions that use the Remove paramters to do something
 impl RemoveParams {
-    /// processes and performs the remove action using the given parameters
-    fn process_command(&self) {
-        let png_as_bytes = fs::read(&self.path).unwrap();
-        let mut png = Png::try_from(png_as_bytes.as_slice()).unwrap();
+    /// processes and performs the remove action on every given file using the given
+    /// parameters. With several files, returns one line per file and a summary, moving
+    /// past any file that fails. If any file fails: return the lines and the summary
+    /// as an error
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<Option<String>> {
+        let (paths, chunk_type) = self.targets()?;
+        if let [path] = paths {
+            return self.remove_from(path, chunk_type, verbosity, max_size);
+        }
+        if self.output_file.is_some() {
+            return Err("--output can only be used with a single file".into());
+        }
+
+        let mut report = Vec::<String>::new();
+        let mut failed = 0;
+
+        // EFFECT: removes from each file, noting whether it succeeded
+        for path in paths {
+            match self.remove_from(path, chunk_type, verbosity, max_size) {
+                Ok(summary) => report.push(format!(
+                    "{}: {}",
+                    path.display(),
+                    summary.unwrap_or_else(|| String::from("ok"))
+                )),
+                Err(err) => {
+                    failed += 1;
+                    report.push(format!("{}: error: {}", path.display(), err));
+                }
+            }
+        }
+        let summary = format!("{} succeeded, {} failed", paths.len() - failed, failed);
+
+        report.push(summary);
+        if failed > 0 {
+            return Err(report.join("\n").into());
+        }
+        Ok(Some(report.join("\n")))
+    }
+
+    /// Splits the positional arguments into the files to remove from and, unless --index
+    /// is given, the ChunkType to remove, which is the last argument
+    fn targets(&self) -> Result<(&[PathBuf], Option<&str>)> {
+        if self.index.is_some() {
+            return Ok((&self.paths, None));
+        }
 
-        png.remove_first_chunk(&self.chunk_type).unwrap();
-        fs::write(&self.path, png.as_bytes()).unwrap();
+        match self.paths.split_last() {
+            Some((chunk_type, paths)) if !paths.is_empty() => {
+                let chunk_type = chunk_type
+                    .to_str()
+                    .ok_or("chunk type must be valid UTF-8")?;
+                Ok((paths, Some(chunk_type)))
+            }
+            _ => Err("a chunk type or --index is required".into()),
+        }
+    }
+
+    /// Removes the Chunk this command names from the file at the given path, or every
+    /// Chunk with its ChunkType under --all. Returns a summary of the change instead of
+    /// writing it on a dry run, and how many Chunks were removed under --all unless quiet
+    fn remove_from(
+        &self,
+        path: &Path,
+        chunk_type: Option<&str>,
+        verbosity: Verbosity,
+        max_size: u64,
+    ) -> Result<Option<String>> {
+        let out_path = output_path(
+            path,
+            self.output_file.as_deref(),
+            self.force || self.dry_run,
+        )?;
+        let mut png = read_png_with(path, self.fix_crc, max_size)?;
+        let old_len = png.byte_len();
+        verbosity.progress(&format!("read {} bytes from {}", old_len, path.display()));
+
+        let (action, removed_count) = match (self.index, chunk_type) {
+            (None, Some(chunk_type)) if self.all => {
+                let count = png.remove_all_chunks_by_type(chunk_type)?;
+                if count == 0 {
+                    return Err(format!("no chunk of type '{}' found", chunk_type).into());
+                }
+                (
+                    format!("remove {} chunks '{}'", count, chunk_type),
+                    Some(count),
+                )
+            }
+            (index, chunk_type) => {
+                let removed = match (index, chunk_type) {
+                    (Some(index), _) => png.remove_chunk_at(index)?,
+                    (None, Some(chunk_type)) => png.remove_first_chunk(chunk_type)?,
+                    (None, None) => return Err("a chunk type or --index is required".into()),
+                };
+                let action = format!(
+                    "remove chunk '{}' ({} bytes)",
+                    removed.chunk_type(),
+                    removed.length()
+                );
+                (action, None)
+            }
+        };
+        verbosity.progress(&action);
+        if self.fix_crc {
+            png.recompute_crcs();
+        }
+        let summary = finish_change(
+            out_path,
+            &png,
+            old_len,
+            self.dry_run.then_some(action),
+            self.backup.as_deref(),
+        )?;
+        if summary.is_none() {
+            verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+        }
+
+        Ok(summary.or(removed_count
+            .filter(|_| verbosity != Verbosity::Quiet)
+            .map(|count| format!("Removed {} chunks", count))))
+    }
+}
+
+/// Holds the parameters for the Replace command
+#[derive(Args, Debug)]
+struct ReplaceParams {
+    path: PathBuf,
+    chunk_type: String,
+    message: String,
+    output_file: Option<PathBuf>,
+    /// Prints what would change without writing any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+    /// Copies the file about to be overwritten to its path plus this suffix first
+    #[arg(long, value_name = "SUFFIX", num_args = 0..=1, require_equals = true, default_missing_value = ".bak")]
+    backup: Option<String>,
+    /// Reads the file even if crcs don't match and recalculates every crc before writing
+    #[arg(long)]
+    fix_crc: bool,
+}
+
+/// Functions that use the Replace parameters to do something
+impl ReplaceParams {
+    /// processes and performs the replace action using the given parameters.
+    /// Returns a summary of the change instead of writing it on a dry run
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<Option<String>> {
+        let out_path = output_path(
+            &self.path,
+            self.output_file.as_deref(),
+            self.force || self.dry_run,
+        )?;
+        let mut png = read_png_with(&self.path, self.fix_crc, max_size)?;
+        let old_len = png.byte_len();
+        verbosity.progress(&format!("read {} bytes", old_len));
+        let chunk = Chunk::from_strings(&self.chunk_type, &self.message)?;
+        let action = format!(
+            "replace chunk '{}' with {} bytes",
+            chunk.chunk_type(),
+            chunk.length()
+        );
+
+        png.replace_first_chunk(&self.chunk_type, chunk)?;
+        verbosity.progress(&action);
+        if self.fix_crc {
+            png.recompute_crcs();
+        }
+
+        let summary = finish_change(
+            out_path,
+            &png,
+            old_len,
+            self.dry_run.then_some(action),
+            self.backup.as_deref(),
+        )?;
+        if summary.is_none() {
+            verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+        }
+
+        Ok(summary)
     }
 }
 
@@ -123,15 +955,2205 @@ impl RemoveParams {
 #[derive(Args, Debug)]
 struct PrintParams {
     path: PathBuf,
+    /// Only lists Chunks with this ChunkType. Can be given more than once
+    #[arg(long = "type", value_name = "CODE")]
+    types: Vec<String>,
+    /// Writes the listing to this file instead of printing it
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
 }
 
 /// Functions taht use the Print paramters to do something
 impl PrintParams {
-    /// processes and performs the print action using the given paramters
-    fn process_command(&self) -> String {
-        let png_as_bytes = fs::read(&self.path).unwrap();
-        let png = Png::try_from(png_as_bytes.as_slice()).unwrap();
+    /// processes and performs the print action using the given paramters.
+    /// Returns the listing unless it was written to the output file
+    fn process_command(&self, max_size: u64) -> Result<Option<String>> {
+        let png = read_png(&self.path, max_size)?;
+        let listing = png.summary_of_types(&self.types);
+
+        match &self.output_file {
+            Some(out_path) => {
+                write_output(out_path, listing.as_bytes())?;
+                Ok(None)
+            }
+            None => Ok(Some(listing)),
+        }
+    }
+}
+
+/// Holds the parameters for the Move command
+#[derive(Args, Debug)]
+struct MoveParams {
+    path: PathBuf,
+    /// The index of the Chunk to move
+    from: usize,
+    /// The index the Chunk should end up at
+    to: usize,
+    /// Writes the result to this file instead of the input file
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+}
+
+/// Functions that use the Move parameters to do something
+impl MoveParams {
+    /// processes and performs the move action using the given parameters
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<()> {
+        let out_path = output_path(&self.path, self.output_file.as_deref(), self.force)?;
+        let mut png = read_png(&self.path, max_size)?;
+        verbosity.progress(&format!("read {} bytes", png.byte_len()));
+
+        png.move_chunk(self.from, self.to)?;
+        verbosity.progress(&format!("moved chunk {} to index {}", self.from, self.to));
+        write_png(out_path, &png)?;
+        verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+        Ok(())
+    }
+}
+
+/// Holds the parameters for the Rename command
+#[derive(Args, Debug)]
+struct RenameParams {
+    path: PathBuf,
+    /// The index of the Chunk to rename
+    index: usize,
+    /// The ChunkType the Chunk should have
+    new_type: String,
+    /// Writes the result to this file instead of the input file
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+}
+
+/// Functions that use the Rename parameters to do something
+impl RenameParams {
+    /// processes and performs the rename action using the given parameters
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<()> {
+        let new_type = ChunkType::from_str(&self.new_type)?;
+        let out_path = output_path(&self.path, self.output_file.as_deref(), self.force)?;
+        let mut png = read_png(&self.path, max_size)?;
+        verbosity.progress(&format!("read {} bytes", png.byte_len()));
+
+        png.rename_chunk_type(self.index, new_type.clone())?;
+        verbosity.progress(&format!("renamed chunk {} to '{}'", self.index, new_type));
+        write_png(out_path, &png)?;
+        verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+        Ok(())
+    }
+}
+
+/// Holds the parameters for the Stats command
+#[derive(Args, Debug)]
+struct StatsParams {
+    path: PathBuf,
+}
+
+/// Functions that use the Stats parameters to do something
+impl StatsParams {
+    /// processes and performs the stats action using the given parameters
+    fn process_command(&self, max_size: u64) -> Result<String> {
+        let png = read_png(&self.path, max_size)?;
+
+        Ok(describe_stats(&png))
+    }
+}
+
+/// Formats the number of Chunks in the given Png, its size, and how much of that size is
+/// Chunk data versus overhead: 12 bytes per Chunk plus the 8 byte signature. Then the
+/// compressed image data size, the raw image size from IHDR, and how they compare
+fn describe_stats(png: &Png) -> String {
+    let data_size = png.total_data_size();
+    let overhead = Png::STANDARD_HEADER.len() as u64 + 12 * png.len() as u64;
+    let raw_image_size = match png.raw_image_size() {
+        Some(size) => format!("{} bytes", size),
+        None => String::from("unknown"),
+    };
+    let compression_ratio = match png.compression_ratio() {
+        Some(ratio) => format!("{:.2}", ratio),
+        None => String::from("unknown"),
+    };
+
+    format!(
+        "Chunks: {}\nFile size: {} bytes\nData: {} bytes\nOverhead: {} bytes\n\
+         Image data: {} bytes\nRaw image: {}\nCompression ratio: {}",
+        png.len(),
+        data_size + overhead,
+        data_size,
+        overhead,
+        png.idat_size(),
+        raw_image_size,
+        compression_ratio
+    )
+}
+
+/// Holds the parameters for the Find command
+#[derive(Args, Debug)]
+struct FindParams {
+    path: PathBuf,
+    /// The text to search for in the data of each Chunk
+    needle: String,
+    /// Treats the needle as hex digits instead of text
+    #[arg(long)]
+    bytes: bool,
+}
+
+/// Functions that use the Find parameters to do something
+impl FindParams {
+    /// processes and performs the find action using the given parameters.
+    /// If the needle is empty or isn't valid hex with --bytes: return an error
+    fn process_command(&self, max_size: u64) -> Result<String> {
+        let needle = if self.bytes {
+            hex_to_bytes(&self.needle).ok_or("needle is not valid hex")?
+        } else {
+            self.needle.as_bytes().to_vec()
+        };
+        if needle.is_empty() {
+            return Err("needle must not be empty".into());
+        }
+        let png = read_png(&self.path, max_size)?;
+
+        Ok(find_chunks(&png, &needle))
+    }
+}
+
+/// Formats the index and ChunkType of every Chunk in the given Png whose data contains
+/// the given non-empty needle, one per line
+fn find_chunks(png: &Png, needle: &[u8]) -> String {
+    let matches: Vec<String> = png
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| {
+            chunk
+                .data()
+                .windows(needle.len())
+                .any(|part| part == needle)
+        })
+        .map(|(idx, chunk)| format!("{}\t{}", idx, chunk.chunk_type()))
+        .collect();
+
+    if matches.is_empty() {
+        return String::from("no matches");
+    }
+
+    matches.join("\n")
+}
+
+/// Holds the parameters for the Completions command
+#[derive(Args, Debug)]
+struct CompletionsParams {
+    /// The shell to print the completion script for
+    shell: Shell,
+}
+
+/// Functions that use the Completions parameters to do something
+impl CompletionsParams {
+    /// processes and performs the completions action using the given parameters
+    fn process_command(&self) -> Result<String> {
+        let mut script = Vec::<u8>::new();
+        clap_complete::generate(self.shell, &mut Cli::command(), "pngme", &mut script);
+
+        Ok(String::from_utf8(script)?)
+    }
+}
+
+/// Holds the parameters for the Diff command
+#[derive(Args, Debug)]
+struct DiffParams {
+    /// The file to compare from
+    a: PathBuf,
+    /// The file to compare to
+    b: PathBuf,
+}
+
+/// Functions that use the Diff parameters to do something
+impl DiffParams {
+    /// processes and performs the diff action using the given parameters
+    fn process_command(&self, max_size: u64) -> Result<String> {
+        let a = read_png(&self.a, max_size)?;
+        let b = read_png(&self.b, max_size)?;
+
+        Ok(diff_chunks(&a, &b))
+    }
+}
+
+/// Formats how the Chunks of Png b differ from those of Png a, one per line: "-" for a
+/// Chunk only in a, "+" for a Chunk only in b, and "~" for a Chunk whose data or crc
+/// changed. Chunks are matched by position, and when their ChunkTypes differ, by whichever
+/// ChunkType shows up again further along the other Png
+fn diff_chunks(a: &Png, b: &Png) -> String {
+    let (a_chunks, b_chunks) = (a.chunks(), b.chunks());
+    let (mut a_idx, mut b_idx) = (0, 0);
+    let mut lines = Vec::<String>::new();
+    let shows_up_in = |chunks: &[Chunk], chunk: &Chunk| {
+        chunks
+            .iter()
+            .any(|other| other.chunk_type() == chunk.chunk_type())
+    };
+
+    // EFFECT: walks both lists of Chunks together, reporting each difference
+    while a_idx < a_chunks.len() && b_idx < b_chunks.len() {
+        let (a_chunk, b_chunk) = (&a_chunks[a_idx], &b_chunks[b_idx]);
+
+        if a_chunk.chunk_type() == b_chunk.chunk_type() {
+            let changes = match (
+                a_chunk.data() != b_chunk.data(),
+                a_chunk.crc() != b_chunk.crc(),
+            ) {
+                (true, true) => Some("data and crc differ"),
+                (true, false) => Some("data differs"),
+                (false, true) => Some("crc differs"),
+                (false, false) => None,
+            };
+            if let Some(changes) = changes {
+                lines.push(format!(
+                    "~ {}  {}  {}",
+                    b_idx,
+                    b_chunk.chunk_type(),
+                    changes
+                ));
+            }
+            a_idx += 1;
+            b_idx += 1;
+        } else if shows_up_in(&b_chunks[b_idx..], a_chunk) {
+            lines.push(format!("+ {}  {}", b_idx, b_chunk.chunk_type()));
+            b_idx += 1;
+        } else {
+            lines.push(format!("- {}  {}", a_idx, a_chunk.chunk_type()));
+            a_idx += 1;
+        }
+    }
+    lines.extend(
+        a_chunks[a_idx..]
+            .iter()
+            .enumerate()
+            .map(|(offset, chunk)| format!("- {}  {}", a_idx + offset, chunk.chunk_type())),
+    );
+    lines.extend(
+        b_chunks[b_idx..]
+            .iter()
+            .enumerate()
+            .map(|(offset, chunk)| format!("+ {}  {}", b_idx + offset, chunk.chunk_type())),
+    );
+
+    if lines.is_empty() {
+        return String::from("no differences");
+    }
+
+    lines.join("\n")
+}
+
+/// Holds the parameters for the List command
+#[derive(Args, Debug)]
+struct ListParams {
+    path: PathBuf,
+    /// Adds a column describing each standard ChunkType
+    #[arg(long)]
+    describe: bool,
+}
+
+/// Functions that use the List parameters to do something
+impl ListParams {
+    /// processes and performs the list action using the given parameters,
+    /// formatting the listing as JSON if asked to and coloring it under the given
+    /// ColorChoice otherwise. If ignore_crc is set, Chunks with crc mismatches are read anyway
+    fn process_command(
+        &self,
+        json: bool,
+        color: ColorChoice,
+        ignore_crc: bool,
+        max_size: u64,
+    ) -> Result<String> {
+        let png = read_png_with(&self.path, ignore_crc, max_size)?;
+
+        if json {
+            return Ok(serde_json::to_string_pretty(&chunk_summaries(&png))?);
+        }
+        Ok(list_chunks(&png, self.describe, color))
+    }
+}
+
+/// The fields of a Chunk that list shows, in a form that can be serialized
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct ChunkSummary {
+    index: usize,
+    #[serde(rename = "type")]
+    chunk_type: String,
+    length: u32,
+    crc: u32,
+}
+
+/// Builds a ChunkSummary for every Chunk in the given Png
+fn chunk_summaries(png: &Png) -> Vec<ChunkSummary> {
+    png.iter()
+        .enumerate()
+        .map(|(index, chunk)| ChunkSummary {
+            index,
+            chunk_type: chunk.chunk_type().to_string(),
+            length: chunk.length(),
+            crc: chunk.crc(),
+        })
+        .collect()
+}
+
+/// The ANSI color codes list uses for critical ChunkTypes, ancillary ChunkTypes,
+/// and Chunks whose crc doesn't match
+const CRITICAL_COLOR: u8 = 32;
+const ANCILLARY_COLOR: u8 = 36;
+const BAD_CRC_COLOR: u8 = 31;
+
+/// Formats one line per Chunk in the given Png holding its index, ChunkType, length and crc.
+/// If describe is set, lines for standard ChunkTypes end with a description of the ChunkType.
+/// Under the given ColorChoice, ChunkTypes are colored by whether they are critical and lines
+/// of Chunks with a bad crc are colored whole
+fn list_chunks(png: &Png, describe: bool, color: ColorChoice) -> String {
+    png.chunks()
+        .iter()
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let crc_is_valid = chunk.crc_is_valid();
+            let chunk_type = chunk.chunk_type().to_string();
+            let chunk_type = if !crc_is_valid {
+                chunk_type
+            } else if chunk.chunk_type().is_critical() {
+                color.paint(&chunk_type, CRITICAL_COLOR)
+            } else {
+                color.paint(&chunk_type, ANCILLARY_COLOR)
+            };
+            let line = format!(
+                "{}  {}  {}  {:#010x}",
+                idx,
+                chunk_type,
+                chunk.length(),
+                chunk.crc()
+            );
+            let line = match chunk.chunk_type().standard_name() {
+                Some(name) if describe => format!("{}  {}", line, name),
+                _ => line,
+            };
+            match crc_is_valid {
+                true => line,
+                false => color.paint(&line, BAD_CRC_COLOR),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Holds the parameters for the DumpChunk command
+#[derive(Args, Debug)]
+struct DumpChunkParams {
+    path: PathBuf,
+    chunk_type: String,
+    /// Where to write the Chunk's length, ChunkType, data, and crc
+    out: PathBuf,
+}
+
+/// Functions that use the DumpChunk parameters to do something
+impl DumpChunkParams {
+    /// processes and performs the dump-chunk action using the given parameters
+    fn process_command(&self, max_size: u64) -> Result<()> {
+        let png = read_png(&self.path, max_size)?;
+        let chunk = png
+            .chunk_by_type(&self.chunk_type)
+            .ok_or_else(|| format!("no chunk of type '{}' found", self.chunk_type))?;
+
+        write_output(&self.out, &chunk.as_bytes())
+    }
+}
+
+/// Holds the parameters for the Export command
+#[derive(Args, Debug)]
+struct ExportParams {
+    path: PathBuf,
+    chunk_type: String,
+    /// Where to write the Chunk's data
+    out: PathBuf,
+    /// Exports the last Chunk with the ChunkType instead of the first
+    #[arg(long)]
+    last: bool,
+}
+
+/// Functions that use the Export parameters to do something
+impl ExportParams {
+    /// processes and performs the export action using the given parameters.
+    /// If ignore_crc is set, Chunks with crc mismatches are read anyway
+    fn process_command(&self, ignore_crc: bool, max_size: u64) -> Result<()> {
+        let png = read_png_with(&self.path, ignore_crc, max_size)?;
+        let chunk = find_chunk(&png, &self.chunk_type, self.last)?;
+
+        write_output(&self.out, chunk.data())
+    }
+}
+
+/// Holds the parameters for the Inject command
+#[derive(Args, Debug)]
+struct InjectParams {
+    path: PathBuf,
+    /// A file holding a single Chunk's length, ChunkType, data, and crc
+    chunk_file: PathBuf,
+    /// Writes the result to this file instead of the input file
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+}
+
+/// Functions that use the Inject parameters to do something
+impl InjectParams {
+    /// processes and performs the inject action using the given parameters
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<()> {
+        let out_path = output_path(&self.path, self.output_file.as_deref(), self.force)?;
+        let mut png = read_png(&self.path, max_size)?;
+        verbosity.progress(&format!("read {} bytes", png.byte_len()));
+        let chunk = Chunk::try_from(&read_input(&self.chunk_file, max_size)?)?;
+
+        let chunk_type = chunk.chunk_type().clone();
+        png.insert_before_end(chunk)?;
+        verbosity.progress(&format!("injected chunk '{}'", chunk_type));
+        write_png(out_path, &png)?;
+        verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+        Ok(())
+    }
+}
+
+/// Holds the parameters for the Count command
+#[derive(Args, Debug)]
+struct CountParams {
+    path: PathBuf,
+    /// Only count Chunks with this ChunkType, printing just the number
+    chunk_type: Option<String>,
+}
+
+/// Functions that use the Count parameters to do something
+impl CountParams {
+    /// processes and performs the count action using the given parameters,
+    /// formatting the counts as JSON if asked to
+    fn process_command(&self, json: bool, max_size: u64) -> Result<String> {
+        let png = read_png(&self.path, max_size)?;
+
+        if json {
+            let mut counts = tally_chunks(&png);
+            if let Some(chunk_type) = &self.chunk_type {
+                let count = counts.get(chunk_type).copied().unwrap_or(0);
+                counts = BTreeMap::from([(chunk_type.clone(), count)]);
+            }
+            return Ok(serde_json::to_string_pretty(&counts)?);
+        }
+        Ok(count_chunks(&png, self.chunk_type.as_deref()))
+    }
+}
+
+/// Counts how many Chunks of each ChunkType are in the given Png
+fn tally_chunks(png: &Png) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::<String, usize>::new();
+
+    // EFFECT: tallies each Chunk under its ChunkType
+    for chunk in png.chunks() {
+        *counts.entry(chunk.chunk_type().to_string()).or_default() += 1;
+    }
+
+    counts
+}
+
+/// Formats how many Chunks of each ChunkType are in the given Png, sorted by ChunkType.
+/// If a ChunkType is given, only the number of Chunks with that ChunkType is formatted
+fn count_chunks(png: &Png, chunk_type: Option<&str>) -> String {
+    let counts = tally_chunks(png);
+
+    match chunk_type {
+        Some(chunk_type) => counts.get(chunk_type).copied().unwrap_or(0).to_string(),
+        None => counts
+            .iter()
+            .map(|(chunk_type, count)| format!("{}  {}", chunk_type, count))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+/// Holds the parameters for the RetypeAll command
+#[derive(Args, Debug)]
+struct RetypeAllParams {
+    path: PathBuf,
+    /// The private, ancillary ChunkType to give every ancillary Chunk
+    #[arg(long)]
+    to: String,
+    /// Writes the result to this file instead of the input file
+    #[arg(short, long = "output")]
+    output_file: Option<PathBuf>,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+}
+
+/// Functions that use the RetypeAll parameters to do something
+impl RetypeAllParams {
+    /// processes and performs the retype-all action using the given parameters.
+    /// Returns how many Chunks were retyped unless quiet
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<Option<String>> {
+        let chunk_type = ChunkType::from_str(&self.to)?;
+        if chunk_type.is_critical() || chunk_type.is_public() {
+            return Err(format!("'{}' is not a private ancillary chunk type", self.to).into());
+        }
+
+        let out_path = output_path(&self.path, self.output_file.as_deref(), self.force)?;
+        let mut png = read_png(&self.path, max_size)?;
+        verbosity.progress(&format!("read {} bytes", png.byte_len()));
+        let retyped = png.retype_ancillary(&chunk_type);
+        write_png(out_path, &png)?;
+        verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+
+        Ok((verbosity != Verbosity::Quiet)
+            .then(|| format!("Retyped {} chunks to {}", retyped, chunk_type)))
+    }
+}
+
+/// Holds the parameters for the Scrub command
+#[derive(Args, Debug)]
+struct ScrubParams {
+    path: PathBuf,
+    output_file: Option<PathBuf>,
+    /// Keeps the standard tEXt, zTXt, and iTXt Chunks
+    #[arg(long)]
+    keep_text: bool,
+    /// Prints what would change without writing any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Allows overwriting the input file when no output file is given
+    #[arg(short, long)]
+    force: bool,
+    /// Copies the file about to be overwritten to its path plus this suffix first
+    #[arg(long, value_name = "SUFFIX", num_args = 0..=1, require_equals = true, default_missing_value = ".bak")]
+    backup: Option<String>,
+}
+
+/// Functions that use the Scrub parameters to do something
+impl ScrubParams {
+    /// processes and performs the scrub action using the given parameters.
+    /// Returns a summary of the change on a dry run, otherwise how many Chunks were
+    /// removed unless quiet
+    fn process_command(&self, verbosity: Verbosity, max_size: u64) -> Result<Option<String>> {
+        let out_path = output_path(
+            &self.path,
+            self.output_file.as_deref(),
+            self.force || self.dry_run,
+        )?;
+        let mut png = read_png(&self.path, max_size)?;
+        let old_len = png.byte_len();
+        verbosity.progress(&format!("read {} bytes", old_len));
+        let removed = png.strip_ancillary(self.keep_text);
+        let action = format!("remove {} ancillary chunks", removed);
+        verbosity.progress(&action);
+
+        let summary = finish_change(
+            out_path,
+            &png,
+            old_len,
+            self.dry_run.then_some(action),
+            self.backup.as_deref(),
+        )?;
+        if summary.is_some() {
+            return Ok(summary);
+        }
+        verbosity.progress(&format!("wrote {} bytes", png.byte_len()));
+
+        Ok(
+            (verbosity != Verbosity::Quiet)
+                .then(|| format!("Removed {} ancillary chunks", removed)),
+        )
+    }
+}
+
+/// Holds the parameters for the Detect command
+#[derive(Args, Debug)]
+struct DetectParams {
+    path: PathBuf,
+}
+
+/// Functions that use the Detect parameters to do something
+impl DetectParams {
+    /// processes and performs the detect action using the given parameters.
+    /// A file that only parses up to IEND has the bytes after it reported as a sign too
+    fn process_command(&self, max_size: u64) -> Result<String> {
+        let bytes = read_input(&self.path, max_size)?;
+        let (png, trailing_len) = match Png::try_from(bytes.as_slice()) {
+            Ok(png) => (png, 0),
+            Err(_) => {
+                let (png, trailing) = Png::from_bytes_with_trailing(&bytes)?;
+                let trailing_len = trailing.len();
+                (png, trailing_len)
+            }
+        };
+
+        let mut signs = png.hidden_data_signs();
+        if trailing_len > 0 {
+            signs.push(format!(
+                "{} bytes of trailing data after IEND",
+                trailing_len
+            ));
+        }
+        Ok(describe_hidden_data_signs(&signs))
+    }
+}
+
+/// Formats how confident we are that a Png holds hidden data from the signs found in it
+fn describe_hidden_data_signs(signs: &[String]) -> String {
+    let verdict = match signs.len() {
+        0 => "Unlikely to contain hidden data",
+        1 => "May contain hidden data",
+        _ => "Likely contains hidden data",
+    };
+
+    signs
+        .iter()
+        .fold(String::from(verdict), |mut report, sign| {
+            report.push_str("\n  - ");
+            report.push_str(sign);
+            report
+        })
+}
+
+/// Holds the parameters for the Verify command
+#[derive(Args, Debug)]
+struct VerifyParams {
+    path: PathBuf,
+}
+
+/// Functions that use the Verify parameters to do something
+impl VerifyParams {
+    /// processes and performs the verify action using the given parameters.
+    /// If the file has any problems: return an error listing them
+    fn process_command(&self, max_size: u64) -> Result<String> {
+        let png = Png::try_from_lenient(&read_input(&self.path, max_size)?)?;
+        let problems = png.integrity_problems();
+
+        if problems.is_empty() {
+            return Ok(format!("OK: all {} chunks are valid", png.chunks().len()));
+        }
+
+        Err(problems
+            .iter()
+            .fold(
+                match problems.len() {
+                    1 => String::from("found 1 problem"),
+                    count => format!("found {} problems", count),
+                },
+                |mut report, problem| {
+                    report.push_str("\n  - ");
+                    report.push_str(problem);
+                    report
+                },
+            )
+            .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn testing_png() -> Png {
+        let chunks = [
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hidden".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+        ];
+        let bytes: Vec<u8> = Png::STANDARD_HEADER
+            .into_iter()
+            .chain(chunks.iter().flat_map(|chunk| chunk.as_bytes()))
+            .collect();
+
+        Png::try_from(bytes).unwrap()
+    }
+
+    /// Writes the testing png to a file named after the given test in a new temp dir and
+    /// returns the dir with the file's path
+    fn testing_png_file(test_name: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(format!("{test_name}.png"));
+        fs::write(&path, testing_png().as_bytes()).unwrap();
+        (dir, path)
+    }
+
+    /// Returns the EncodeParams of storing the given message under the given ChunkType in
+    /// the file at the given path, overwriting it, with every other option left at its default
+    fn encode_params(path: &Path, chunk_type: &str, message: &str) -> EncodeParams {
+        EncodeParams {
+            path: path.to_path_buf(),
+            chunk_type: Some(String::from(chunk_type)),
+            message: Some(String::from(message)),
+            output_file: None,
+            output: None,
+            message_file: None,
+            auto_type: false,
+            frame: false,
+            field: Vec::new(),
+            compress: false,
+            password: None,
+            header: false,
+            repeat: 1,
+            at: None,
+            dry_run: false,
+            force: true,
+            backup: None,
+            fix_crc: false,
+        }
+    }
+
+    #[test]
+    fn test_count_chunks() {
+        let mut png = testing_png();
+        png.insert_before_end(Chunk::new(
+            ChunkType::from_str("ruSt").unwrap(),
+            b"again".to_vec(),
+        ))
+        .unwrap();
+
+        assert_eq!(count_chunks(&png, None), "IEND  1\nIHDR  1\nruSt  2");
+        assert_eq!(count_chunks(&png, Some("ruSt")), "2");
+        assert_eq!(count_chunks(&png, Some("IDAT")), "0");
+    }
+
+    #[test]
+    fn test_decode_to_output_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("decode_output.png");
+        let out = path.with_extension("bin");
+        let mut png = testing_png();
+        png.insert_before_end(Chunk::new(
+            ChunkType::from_str("biNa").unwrap(),
+            vec![0, 159, 146, 150, 255],
+        ))
+        .unwrap();
+        write_png(&path, &png).unwrap();
+
+        let params = DecodeParams {
+            path: path.clone(),
+            chunk_type: Some(String::from("biNa")),
+            index: None,
+            last: false,
+            encoding: Encoding::Utf8,
+            output_file: Some(out.clone()),
+            password: None,
+            text_only: false,
+        };
+
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            None
+        );
+        assert_eq!(fs::read(&out).unwrap(), vec![0, 159, 146, 150, 255]);
+    }
+
+    #[test]
+    fn test_decode_text_only() {
+        let (_dir, path) = testing_png_file("decode_text_only");
+        let mut png = testing_png();
+        png.insert_before_end(Chunk::new(
+            ChunkType::from_str("biNa").unwrap(),
+            vec![0, 159, 146, 150, 255],
+        ))
+        .unwrap();
+        write_png(&path, &png).unwrap();
+        let out = path.with_extension("bin");
+
+        let mut params = DecodeParams {
+            path: path.clone(),
+            chunk_type: Some(String::from("ruSt")),
+            index: None,
+            last: false,
+            encoding: Encoding::Utf8,
+            output_file: None,
+            password: None,
+            text_only: true,
+        };
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some(String::from("hidden"))
+        );
+
+        params.chunk_type = Some(String::from("biNa"));
+        params.output_file = Some(out.clone());
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap_err()
+                .to_string(),
+            "chunk data is not valid UTF-8"
+        );
+        assert!(!out.exists());
+    }
+
+    #[test]
+    fn test_encode_compressed_round_trip() {
+        let (_dir, path) = testing_png_file("encode_compressed");
+        let message = "a very repetitive message ".repeat(40);
+        let encode = |compress: bool, chunk_type: &str| EncodeParams {
+            compress,
+            ..encode_params(&path, chunk_type, &message)
+        };
+        encode(false, "plAn")
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+        encode(true, "ziPd")
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+
+        let png = read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap();
+        let plain_len = png.chunk_by_type("plAn").unwrap().length();
+        let compressed_len = png.chunk_by_type("ziPd").unwrap().length();
+        assert!(compressed_len < plain_len);
+
+        let decode = DecodeParams {
+            path: path.clone(),
+            chunk_type: Some(String::from("ziPd")),
+            index: None,
+            last: false,
+            encoding: Encoding::Utf8,
+            output_file: None,
+            password: None,
+            text_only: false,
+        };
+        assert_eq!(
+            decode
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some(message)
+        );
+    }
+
+    #[test]
+    fn test_encode_dry_run_leaves_file_unchanged() {
+        let (_dir, path) = testing_png_file("encode_dry_run");
+        let before = fs::read(&path).unwrap();
+
+        let cli = Cli::try_parse_from([
+            "pngme",
+            "encode",
+            path.to_str().unwrap(),
+            "ruSt",
+            "a new message",
+            "--dry-run",
+        ])
+        .unwrap();
+        let Commands::Encode(params) = &cli.command else {
+            panic!("expected the encode command");
+        };
+
+        let summary = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            summary,
+            format!(
+                "would append chunk 'ruSt' (13 bytes); file would grow from {} to {} bytes",
+                before.len(),
+                before.len() + 25
+            )
+        );
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_encode_repeat() {
+        let (_dir, path) = testing_png_file("encode_repeat");
+        let encode = |repeat: &str| {
+            let cli = Cli::try_parse_from([
+                "pngme",
+                "encode",
+                path.to_str().unwrap(),
+                "rePt",
+                "again",
+                "--force",
+                "--repeat",
+                repeat,
+            ])
+            .unwrap();
+            let Commands::Encode(params) = cli.command else {
+                panic!("expected the encode command");
+            };
+            params.process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+        };
+        encode("3").unwrap();
+
+        let png = read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap();
+        assert_eq!(count_chunks(&png, Some("rePt")), "3");
+        assert_eq!(png.chunks()[png.len() - 1].chunk_type().to_string(), "IEND");
+
+        assert!(encode("0").is_err());
+        assert!(encode("10001").is_err());
+        assert_eq!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap().len(),
+            png.len()
+        );
+    }
+
+    #[test]
+    fn test_encode_at_index() {
+        let (_dir, path) = testing_png_file("encode_at");
+        let encode = |at: &str| {
+            let cli = Cli::try_parse_from([
+                "pngme",
+                "encode",
+                path.to_str().unwrap(),
+                "frSt",
+                "up front",
+                "--force",
+                "--at",
+                at,
+            ])
+            .unwrap();
+            let Commands::Encode(params) = cli.command else {
+                panic!("expected the encode command");
+            };
+            params.process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+        };
+        encode("1").unwrap();
+
+        assert_eq!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_types(),
+            ["IHDR", "frSt", "ruSt", "IEND"]
+        );
+
+        assert!(encode("0").is_err());
+        assert!(encode("4").is_err());
+        assert_eq!(read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_find_encoded_message() {
+        let (_dir, path) = testing_png_file("find");
+        encode_params(&path, "fiNd", "the treasure is under the oak")
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+
+        let find = |needle: &str, bytes: bool| {
+            FindParams {
+                path: path.clone(),
+                needle: String::from(needle),
+                bytes,
+            }
+            .process_command(DEFAULT_MAX_INPUT_SIZE)
+        };
+        assert_eq!(find("under the", false).unwrap(), "2\tfiNd");
+        assert_eq!(find("d", false).unwrap(), "1\truSt\n2\tfiNd");
+        assert_eq!(find("6f616b", true).unwrap(), "2\tfiNd");
+        assert_eq!(find("buried", false).unwrap(), "no matches");
+        assert!(find("", false).is_err());
+        assert!(find("6f6", true).is_err());
+    }
+
+    #[test]
+    fn test_quiet_decode_has_no_prefix() {
+        let cli = Cli::try_parse_from(["pngme", "-q", "decode", "image.png", "ruSt"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Quiet);
+        assert_eq!(decoded_output("hidden", cli.verbosity()), "hidden");
+
+        let cli =
+            Cli::try_parse_from(["pngme", "decode", "image.png", "ruSt", "--verbose"]).unwrap();
+        assert_eq!(cli.verbosity(), Verbosity::Verbose);
+        assert_eq!(
+            decoded_output("hidden", cli.verbosity()),
+            "Your Decoded Picture:\nhidden"
+        );
+
+        assert!(Cli::try_parse_from(["pngme", "-q", "-v", "decode", "image.png", "ruSt"]).is_err());
+    }
+
+    #[test]
+    fn test_bash_completions() {
+        let params = CompletionsParams { shell: Shell::Bash };
+        let script = params.process_command().unwrap();
+
+        for subcommand in ["encode", "decode", "remove", "verify", "find"] {
+            assert!(script.contains(subcommand));
+        }
+        assert!(script.contains("--dry-run"));
+    }
+
+    #[test]
+    fn test_backup_keeps_original_bytes() {
+        let (_dir, path) = testing_png_file("backup");
+        let backup_path = path.with_extension("png.bak");
+        let before = fs::read(&path).unwrap();
+        let remove = |backup: &str| {
+            let cli = Cli::try_parse_from([
+                "pngme",
+                "remove",
+                backup,
+                "--force",
+                path.to_str().unwrap(),
+                "ruSt",
+            ])
+            .unwrap();
+            let Commands::Remove(params) = cli.command else {
+                panic!("expected the remove command");
+            };
+            params.process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+        };
+
+        remove("--backup").unwrap();
+        assert_eq!(fs::read(&backup_path).unwrap(), before);
+        assert!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("ruSt")
+                .is_none()
+        );
+
+        fs::write(&path, &before).unwrap();
+        let err = remove("--backup").unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert_eq!(fs::read(&path).unwrap(), before);
+
+        remove("--backup=.orig").unwrap();
+        assert_eq!(fs::read(path.with_extension("png.orig")).unwrap(), before);
+    }
+
+    #[test]
+    fn test_write_output_replaces_file() {
+        let (_dir, path) = testing_png_file("write_output");
+        write_output(&path, b"new contents").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new contents");
+        let leftovers = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.starts_with(".pngme_write_output.png.pngme-")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_scrub_leaves_critical_chunks() {
+        let (_dir, path) = testing_png_file("scrub");
+        let mut png = read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap();
+        png.insert_before_end(Chunk::from_strings("tEXt", "Comment\0kept").unwrap())
+            .unwrap();
+        png.insert_before_end(Chunk::from_strings("teSt", "dropped").unwrap())
+            .unwrap();
+        write_png(&path, &png).unwrap();
+
+        let params = ScrubParams {
+            path: path.clone(),
+            output_file: None,
+            keep_text: true,
+            dry_run: false,
+            force: true,
+            backup: None,
+        };
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some(String::from("Removed 2 ancillary chunks"))
+        );
+        assert!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("tEXt")
+                .is_some()
+        );
+
+        let params = ScrubParams {
+            keep_text: false,
+            ..params
+        };
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some(String::from("Removed 1 ancillary chunks"))
+        );
+        assert_eq!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_types(),
+            ["IHDR", "IEND"]
+        );
+    }
+
+    #[test]
+    fn test_scrub_needs_force() {
+        let (_dir, path) = testing_png_file("scrub_force");
+        let before = fs::read(&path).unwrap();
+        let path_arg = path.to_str().unwrap();
+
+        let scrub = Cli::try_parse_from(["pngme", "scrub", path_arg]).unwrap();
+        assert_eq!(
+            scrub.process_command().unwrap_err().to_string(),
+            "refusing to overwrite input; pass --force or --output"
+        );
+        let Commands::Scrub(params) =
+            Cli::try_parse_from(["pngme", "scrub", path_arg, "--dry-run"])
+                .unwrap()
+                .command
+        else {
+            panic!("expected the scrub command");
+        };
+        assert!(
+            params
+                .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .unwrap()
+                .starts_with("would remove 1 ancillary chunks; file would shrink")
+        );
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_quiet_mutating_commands() {
+        let (_dir, path) = testing_png_file("quiet_mutating");
+
+        let retype = RetypeAllParams {
+            path: path.clone(),
+            to: String::from("quIt"),
+            output_file: None,
+            force: true,
+        };
+        assert_eq!(
+            retype
+                .process_command(Verbosity::Quiet, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            None
+        );
+        let scrub = ScrubParams {
+            path: path.clone(),
+            output_file: None,
+            keep_text: false,
+            dry_run: true,
+            force: false,
+            backup: None,
+        };
+        assert!(
+            scrub
+                .process_command(Verbosity::Quiet, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .unwrap()
+                .starts_with("would remove 1 ancillary chunks")
+        );
+        let scrub = ScrubParams {
+            dry_run: false,
+            force: true,
+            ..scrub
+        };
+        assert_eq!(
+            scrub
+                .process_command(Verbosity::Quiet, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_types(),
+            ["IHDR", "IEND"]
+        );
+    }
+
+    #[test]
+    fn test_decode_with_ignore_crc() {
+        let (_dir, path) = testing_png_file("ignore_crc");
+        let mut bytes = fs::read(&path).unwrap();
+        // the last crc byte of the ruSt chunk, just before the 12 byte IEND chunk
+        let crc_idx = bytes.len() - 13;
+        bytes[crc_idx] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+
+        let decode = |ignore_crc: &str| {
+            let cli = Cli::try_parse_from([
+                "pngme",
+                ignore_crc,
+                "decode",
+                path.to_str().unwrap(),
+                "ruSt",
+            ])
+            .unwrap();
+            let Commands::Decode(params) = &cli.command else {
+                panic!("expected the decode command");
+            };
+            params.process_command(cli.verbosity(), cli.ignore_crc, DEFAULT_MAX_INPUT_SIZE)
+        };
+
+        assert!(decode("--quiet").is_err());
+        assert_eq!(
+            decode("--ignore-crc").unwrap(),
+            Some(String::from("hidden"))
+        );
+    }
+
+    #[test]
+    fn test_diff_chunks() {
+        let png = testing_png();
+        assert_eq!(diff_chunks(&png, &png), "no differences");
+
+        let mut extra = testing_png();
+        extra
+            .insert_before_end(Chunk::from_strings("exTr", "one more").unwrap())
+            .unwrap();
+        assert_eq!(diff_chunks(&png, &extra), "+ 2  exTr");
+        assert_eq!(diff_chunks(&extra, &png), "- 2  exTr");
+
+        let mut changed = testing_png();
+        changed
+            .replace_first_chunk("ruSt", Chunk::from_strings("ruSt", "changed").unwrap())
+            .unwrap();
+        assert_eq!(
+            diff_chunks(&png, &changed),
+            "~ 1  ruSt  data and crc differ"
+        );
+    }
+
+    #[test]
+    fn test_diff_command_against_itself() {
+        let (_dir, path) = testing_png_file("diff");
+        let params = DiffParams {
+            a: path.clone(),
+            b: path.clone(),
+        };
+
+        assert_eq!(
+            params.process_command(DEFAULT_MAX_INPUT_SIZE).unwrap(),
+            "no differences"
+        );
+    }
+
+    #[test]
+    fn test_decode_last_chunk() {
+        let (_dir, path) = testing_png_file("decode_last");
+        let path_str = path.to_str().unwrap();
+        let run = |args: &[&str]| {
+            let cli = Cli::try_parse_from(args).unwrap();
+            match &cli.command {
+                Commands::Encode(params) => {
+                    params.process_command(cli.verbosity(), DEFAULT_MAX_INPUT_SIZE)
+                }
+                Commands::Decode(params) => {
+                    params.process_command(cli.verbosity(), false, DEFAULT_MAX_INPUT_SIZE)
+                }
+                _ => panic!("expected the encode or decode command"),
+            }
+        };
+        run(&["pngme", "encode", path_str, "ruSt", "second", "--force"]).unwrap();
+
+        assert_eq!(
+            run(&["pngme", "decode", path_str, "ruSt"]).unwrap(),
+            Some(String::from("hidden"))
+        );
+        assert_eq!(
+            run(&["pngme", "decode", path_str, "ruSt", "--last"]).unwrap(),
+            Some(String::from("second"))
+        );
+        let err = run(&["pngme", "decode", path_str, "noNe", "--last"]).unwrap_err();
+        assert_eq!(err.to_string(), "no chunk of type 'noNe' found");
+    }
+
+    #[test]
+    fn test_describe_stats() {
+        let png = testing_png();
+        assert_eq!(
+            describe_stats(&png),
+            "Chunks: 3\nFile size: 63 bytes\nData: 19 bytes\nOverhead: 44 bytes\n\
+             Image data: 0 bytes\nRaw image: 0 bytes\nCompression ratio: unknown"
+        );
+        assert_eq!(png.as_bytes().len(), 63);
+
+        // a 2x2 8 bit RGB image is 12 raw bytes, here compressed to 6
+        let ihdr = [0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0];
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), ihdr.to_vec()),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![0; 6]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+        ]);
+        assert!(
+            describe_stats(&png)
+                .ends_with("Image data: 6 bytes\nRaw image: 12 bytes\nCompression ratio: 0.50")
+        );
+    }
+
+    #[test]
+    fn test_move_command() {
+        let (_dir, path) = testing_png_file("move");
+        let params = MoveParams {
+            path: path.clone(),
+            from: 1,
+            to: 2,
+            output_file: None,
+            force: true,
+        };
+
+        let err = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "refusing to move the IEND chunk from its place"
+        );
+        assert_eq!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap().as_bytes(),
+            testing_png().as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_rename_command() {
+        let (_dir, path) = testing_png_file("rename");
+        let params = RenameParams {
+            path: path.clone(),
+            index: 1,
+            new_type: String::from("teNt"),
+            output_file: None,
+            force: true,
+        };
+        params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+
+        let png = read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap();
+        let renamed = png.chunk_by_index(1).unwrap();
+        assert_eq!(renamed.chunk_type().to_string(), "teNt");
+        assert_eq!(renamed.data(), b"hidden");
+        assert_ne!(renamed.crc(), testing_png().chunks()[1].crc());
+
+        let params = RenameParams { index: 2, ..params };
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap_err()
+                .to_string(),
+            "refusing to rename a chunk to or from IEND"
+        );
+    }
+
+    #[test]
+    fn test_rename_needs_force() {
+        let (_dir, path) = testing_png_file("rename_force");
+        let out = path.with_extension("renamed.png");
+        let mut params = RenameParams {
+            path: path.clone(),
+            index: 1,
+            new_type: String::from("teNt"),
+            output_file: None,
+            force: false,
+        };
+
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap_err()
+                .to_string(),
+            "refusing to overwrite input; pass --force or --output"
+        );
+        assert_eq!(fs::read(&path).unwrap(), testing_png().as_bytes());
+
+        params.output_file = Some(out.clone());
+        params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+        assert_eq!(fs::read(&path).unwrap(), testing_png().as_bytes());
+        assert!(
+            read_png(&out, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .contains_chunk_type("teNt")
+        );
+    }
+
+    #[test]
+    fn test_remove_dry_run_summary() {
+        let (_dir, path) = testing_png_file("remove_dry_run");
+        let before = fs::read(&path).unwrap();
+        let params = RemoveParams {
+            paths: vec![path.clone(), PathBuf::from("ruSt")],
+            index: None,
+            all: false,
+            output_file: None,
+            dry_run: true,
+            force: false,
+            backup: None,
+            fix_crc: false,
+        };
+
+        let summary = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap()
+            .unwrap();
+        assert!(summary.starts_with("would remove chunk 'ruSt' (6 bytes); file would shrink"));
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn test_encode_message_file_round_trip() {
+        let (_dir, path) = testing_png_file("encode_message_file");
+        let message_file = path.with_extension("payload");
+        let out = path.with_extension("out.png");
+        let exported = path.with_extension("exported");
+        let payload = vec![0, 159, 146, 150, 255, b'P', b'K', 3, 4];
+        fs::write(&message_file, &payload).unwrap();
+
+        let cli = Cli::try_parse_from([
+            "pngme",
+            "encode",
+            path.to_str().unwrap(),
+            "fiLe",
+            "--message-file",
+            message_file.to_str().unwrap(),
+            "-o",
+            out.to_str().unwrap(),
+        ])
+        .unwrap();
+        cli.process_command().unwrap();
+
+        ExportParams {
+            path: out.clone(),
+            chunk_type: String::from("fiLe"),
+            out: exported.clone(),
+            last: false,
+        }
+        .process_command(false, DEFAULT_MAX_INPUT_SIZE)
+        .unwrap();
+        assert_eq!(fs::read(&exported).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_encode_needs_exactly_one_message() {
+        let both = [
+            "pngme",
+            "encode",
+            "x.png",
+            "ruSt",
+            "hi",
+            "--message-file",
+            "m",
+        ];
+        assert!(Cli::try_parse_from(both).is_err());
+        assert!(Cli::try_parse_from(["pngme", "encode", "x.png", "ruSt"]).is_err());
+        assert!(
+            Cli::try_parse_from(["pngme", "encode", "x.png", "ruSt", "--message-file", "m"])
+                .is_ok()
+        );
+        let two_outputs = [
+            "pngme", "encode", "x.png", "ruSt", "hi", "a.png", "-o", "b.png",
+        ];
+        assert!(Cli::try_parse_from(two_outputs).is_err());
+    }
+
+    #[test]
+    fn test_encode_encrypted_round_trip() {
+        let (_dir, path) = testing_png_file("encode_encrypted");
+        EncodeParams {
+            compress: true,
+            password: Some(String::from("hunter2")),
+            ..encode_params(&path, "seCr", "meet me at noon")
+        }
+        .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+        .unwrap();
+
+        let decode = |password: Option<&str>| DecodeParams {
+            text_only: false,
+            path: path.clone(),
+            chunk_type: Some(String::from("seCr")),
+            index: None,
+            last: false,
+            encoding: Encoding::Utf8,
+            output_file: None,
+            password: password.map(String::from),
+        };
+        assert_eq!(
+            decode(Some("hunter2"))
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some(String::from("meet me at noon"))
+        );
+        assert_eq!(
+            decode(Some("hunter3"))
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap_err()
+                .to_string(),
+            "could not decrypt: wrong password or tampered data"
+        );
+        assert!(
+            decode(None)
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_encode_with_header_round_trip() {
+        let (_dir, path) = testing_png_file("encode_header");
+        let message = "a header says how to undo me ".repeat(10);
+        EncodeParams {
+            compress: true,
+            password: Some(String::from("hunter2")),
+            header: true,
+            ..encode_params(&path, "heAd", &message)
+        }
+        .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+        .unwrap();
+
+        let png = read_png(&path, DEFAULT_MAX_INPUT_SIZE).unwrap();
+        let (header, _) = png
+            .chunk_by_type("heAd")
+            .unwrap()
+            .parse_payload_header()
+            .unwrap();
+        assert_eq!(
+            header,
+            Some(PayloadHeader {
+                compressed: true,
+                encrypted: true
+            })
+        );
+
+        let decode = DecodeParams {
+            path: path.clone(),
+            chunk_type: Some(String::from("heAd")),
+            index: None,
+            last: false,
+            encoding: Encoding::Utf8,
+            output_file: None,
+            password: Some(String::from("hunter2")),
+            text_only: false,
+        };
+        assert_eq!(
+            decode
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some(message)
+        );
+    }
+
+    #[test]
+    fn test_unwrap_payload_trusts_only_a_matching_header() {
+        let payload = |header: Option<PayloadHeader>, body: &[u8]| {
+            let mut data = header.map_or(Vec::new(), |header| header.to_bytes().to_vec());
+            data.extend_from_slice(body);
+            Chunk::new(ChunkType::from_str("heAd").unwrap(), data)
+        };
+        let compressed = compress_marked(b"squeezed");
+
+        assert_eq!(
+            unwrap_payload(&payload(None, &compressed), None).unwrap(),
+            b"squeezed"
+        );
+        assert_eq!(
+            unwrap_payload(&payload(None, b"plain"), Some("unused")).unwrap(),
+            b"plain"
+        );
+        let says_compressed = PayloadHeader {
+            compressed: true,
+            encrypted: false,
+        };
+        assert_eq!(
+            unwrap_payload(&payload(Some(says_compressed), b"plain"), None)
+                .unwrap_err()
+                .to_string(),
+            "payload header disagrees with the chunk data about compression"
+        );
+        assert_eq!(
+            unwrap_payload(&payload(Some(PayloadHeader::default()), &compressed), None)
+                .unwrap_err()
+                .to_string(),
+            "payload header disagrees with the chunk data about compression"
+        );
+        let says_encrypted = PayloadHeader {
+            compressed: false,
+            encrypted: true,
+        };
+        assert_eq!(
+            unwrap_payload(&payload(Some(says_encrypted), b"plain"), Some("pw"))
+                .unwrap_err()
+                .to_string(),
+            "payload header disagrees with the chunk data about encryption"
+        );
+    }
+
+    #[test]
+    fn test_decode_by_index() {
+        let (_dir, path) = testing_png_file("decode_index");
+        let mut params = DecodeParams {
+            path: path.clone(),
+            chunk_type: None,
+            index: Some(0),
+            last: false,
+            encoding: Encoding::Hex,
+            output_file: None,
+            password: None,
+            text_only: false,
+        };
+        assert_eq!(
+            params
+                .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            Some("00".repeat(13))
+        );
+
+        params.index = Some(3);
+        let err = params
+            .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no chunk at index 3: the file has 3 chunks"
+        );
+    }
+
+    #[test]
+    fn test_print_to_output_file() {
+        let (_dir, path) = testing_png_file("print_output");
+        let out = path.with_extension("txt");
+        let params = PrintParams {
+            path: path.clone(),
+            types: Vec::new(),
+            output_file: Some(out.clone()),
+        };
+
+        assert_eq!(
+            params.process_command(DEFAULT_MAX_INPUT_SIZE).unwrap(),
+            None
+        );
+        assert_eq!(
+            fs::read_to_string(&out).unwrap(),
+            format!("{}", testing_png())
+        );
+    }
+
+    #[test]
+    fn test_print_filtered_to_one_type() {
+        let (_dir, path) = testing_png_file("print_type");
+        let params = PrintParams {
+            path: path.clone(),
+            types: vec![String::from("ruSt")],
+            output_file: None,
+        };
+
+        let listing = params
+            .process_command(DEFAULT_MAX_INPUT_SIZE)
+            .unwrap()
+            .unwrap();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Signature: "));
+        assert_eq!(lines[1], "1  ruSt  6  \"hidden\"");
+    }
+
+    #[test]
+    fn test_describe_hidden_data_signs() {
+        assert_eq!(
+            describe_hidden_data_signs(&[]),
+            "Unlikely to contain hidden data"
+        );
+
+        let png = testing_png();
+        assert_eq!(
+            describe_hidden_data_signs(&png.hidden_data_signs()),
+            "May contain hidden data\n  - non-standard chunk 'ruSt' at index 1"
+        );
+    }
+
+    #[test]
+    fn test_detect_trailing_data() {
+        let (_dir, path) = testing_png_file("detect_trailing");
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.extend_from_slice(b"PK\x03\x04hidden zip");
+        fs::write(&path, bytes).unwrap();
+
+        let params = DetectParams { path: path.clone() };
+        assert_eq!(
+            params.process_command(DEFAULT_MAX_INPUT_SIZE).unwrap(),
+            "Likely contains hidden data\n  - non-standard chunk 'ruSt' at index 1\n  \
+             - 14 bytes of trailing data after IEND"
+        );
+    }
+
+    #[test]
+    fn test_encoding_format() {
+        let data = [0x68, 0x69, 0x00, 0xff];
+        assert_eq!(Encoding::Hex.format(&data).unwrap(), "686900ff");
+        assert_eq!(Encoding::Base64.format(&data).unwrap(), "aGkA/w==");
+        assert_eq!(Encoding::Utf8.format(b"hi").unwrap(), "hi");
+        assert!(Encoding::Utf8.format(&data).is_err());
+    }
+
+    #[test]
+    fn test_output_path() {
+        let stdin = Path::new("-");
+        let file = Path::new("in.png");
+        let out = Path::new("out.png");
+
+        assert_eq!(output_path(file, None, true).unwrap(), file);
+        assert_eq!(output_path(file, Some(out), true).unwrap(), out);
+        assert_eq!(output_path(file, Some(out), false).unwrap(), out);
+        assert_eq!(output_path(stdin, Some(out), true).unwrap(), out);
+        assert!(output_path(stdin, None, true).is_err());
+        assert_eq!(
+            output_path(file, None, false).unwrap_err().to_string(),
+            "refusing to overwrite input; pass --force or --output"
+        );
+    }
+
+    #[test]
+    fn test_remove_from_stdin_needs_output() {
+        let params = RemoveParams {
+            paths: vec![PathBuf::from("-"), PathBuf::from("ruSt")],
+            index: None,
+            all: false,
+            output_file: None,
+            dry_run: false,
+            force: true,
+            backup: None,
+            fix_crc: false,
+        };
+
+        let err = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err();
+        assert!(err.to_string().contains("requires an output file"));
+    }
+
+    #[test]
+    fn test_overwrite_needs_force() {
+        let (_dir, path) = testing_png_file("needs_force");
+        let before = fs::read(&path).unwrap();
+        let path_arg = path.to_str().unwrap();
+
+        let encode = Cli::try_parse_from(["pngme", "encode", path_arg, "ruSt", "new"]).unwrap();
+        assert_eq!(
+            encode.process_command().unwrap_err().to_string(),
+            "refusing to overwrite input; pass --force or --output"
+        );
+        // EFFECT: checks that every command that rewrites the file refuses to without --force
+        for args in [
+            vec!["remove", path_arg, "ruSt"],
+            vec!["move", path_arg, "1", "0"],
+            vec!["inject", path_arg, path_arg],
+            vec!["retype-all", path_arg, "--to", "ruSt"],
+        ] {
+            let cli = Cli::try_parse_from(["pngme"].into_iter().chain(args)).unwrap();
+            assert_eq!(
+                cli.process_command().unwrap_err().to_string(),
+                "refusing to overwrite input; pass --force or --output"
+            );
+        }
+        assert_eq!(fs::read(&path).unwrap(), before);
+
+        let out = path.with_file_name("removed.png");
+        let remove_to = Cli::try_parse_from([
+            "pngme",
+            "remove",
+            path_arg,
+            "ruSt",
+            "--output",
+            out.to_str().unwrap(),
+        ])
+        .unwrap();
+        remove_to.process_command().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), before);
+        assert_eq!(
+            read_png(&out, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_types(),
+            ["IHDR", "IEND"]
+        );
+
+        let forced = Cli::try_parse_from(["pngme", "remove", path_arg, "ruSt", "-f"]).unwrap();
+        forced.process_command().unwrap();
+        assert!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("ruSt")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_remove_by_index() {
+        let (_dir, path) = testing_png_file("remove_index");
+        let params = RemoveParams {
+            paths: vec![path.clone()],
+            index: Some(1),
+            all: false,
+            output_file: None,
+            dry_run: false,
+            force: true,
+            backup: None,
+            fix_crc: false,
+        };
+        params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+        assert!(
+            read_png(&path, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("ruSt")
+                .is_none()
+        );
+
+        let err = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "refusing to remove the IEND chunk");
+    }
+
+    #[test]
+    fn test_remove_targets() {
+        let params = |args: &[&str], index: Option<usize>| RemoveParams {
+            paths: args.iter().map(PathBuf::from).collect(),
+            index,
+            all: false,
+            output_file: None,
+            dry_run: false,
+            force: true,
+            backup: None,
+            fix_crc: false,
+        };
+
+        let by_type = params(&["a.png", "b.png", "ruSt"], None);
+        let (paths, chunk_type) = by_type.targets().unwrap();
+        assert_eq!(paths, [PathBuf::from("a.png"), PathBuf::from("b.png")]);
+        assert_eq!(chunk_type, Some("ruSt"));
+
+        let by_index = params(&["a.png", "b.png"], Some(1));
+        assert_eq!(by_index.targets().unwrap(), (&by_index.paths[..], None));
+
+        assert!(params(&["a.png"], None).targets().is_err());
+        assert!(Cli::try_parse_from(["pngme", "remove", "--index", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_remove_from_many_files() {
+        let (_first_dir, first) = testing_png_file("remove_many_first");
+        let (_second_dir, second) = testing_png_file("remove_many_second");
+        let missing = first.with_file_name("missing.png");
+        let params = RemoveParams {
+            paths: vec![first.clone(), second.clone(), PathBuf::from("ruSt")],
+            index: None,
+            all: false,
+            output_file: None,
+            dry_run: false,
+            force: true,
+            backup: None,
+            fix_crc: false,
+        };
+
+        let report = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap()
+            .unwrap();
+        assert!(report.ends_with("2 succeeded, 0 failed"));
+        assert!(
+            read_png(&first, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("ruSt")
+                .is_none()
+        );
+        assert!(
+            read_png(&second, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("ruSt")
+                .is_none()
+        );
+
+        fs::write(&first, testing_png().as_bytes()).unwrap();
+        let params = RemoveParams {
+            paths: vec![missing, first.clone(), PathBuf::from("ruSt")],
+            index: None,
+            all: false,
+            output_file: None,
+            dry_run: false,
+            force: true,
+            backup: None,
+            fix_crc: false,
+        };
+        let err = params
+            .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err();
+        let report = err.to_string();
+        assert!(report.contains(": error: "));
+        assert!(report.contains(&format!("{}: ok", first.display())));
+        assert!(report.ends_with("1 succeeded, 1 failed"));
+        assert!(
+            read_png(&first, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap()
+                .chunk_by_type("ruSt")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_dump_chunk_reparses() {
+        let (_dir, path) = testing_png_file("dump_chunk");
+        let out = path.with_extension("chunk");
+        let params = DumpChunkParams {
+            path: path.clone(),
+            chunk_type: String::from("ruSt"),
+            out: out.clone(),
+        };
+
+        params.process_command(DEFAULT_MAX_INPUT_SIZE).unwrap();
+        let dumped = Chunk::try_from(&fs::read(&out).unwrap()).unwrap();
+
+        assert_eq!(&dumped, testing_png().chunk_by_type("ruSt").unwrap());
+    }
+
+    #[test]
+    fn test_export_writes_raw_data() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("export.png");
+        let out = path.with_extension("bin");
+        let payload = vec![0, 159, 146, 150, 255, b'P', b'K'];
+        let mut png = testing_png();
+        png.insert_before_end(Chunk::new(
+            ChunkType::from_str("fiLe").unwrap(),
+            payload.clone(),
+        ))
+        .unwrap();
+        write_png(&path, &png).unwrap();
+
+        let mut params = ExportParams {
+            path: path.clone(),
+            chunk_type: String::from("fiLe"),
+            out: out.clone(),
+            last: false,
+        };
+        params
+            .process_command(false, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap();
+        assert_eq!(fs::read(&out).unwrap(), payload);
+
+        params.chunk_type = String::from("noNe");
+        assert!(
+            params
+                .process_command(false, DEFAULT_MAX_INPUT_SIZE)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_inject_dumped_chunk() {
+        let (_source_dir, source) = testing_png_file("inject_source");
+        let chunk_file = source.with_extension("chunk");
+        DumpChunkParams {
+            path: source.clone(),
+            chunk_type: String::from("ruSt"),
+            out: chunk_file.clone(),
+        }
+        .process_command(DEFAULT_MAX_INPUT_SIZE)
+        .unwrap();
+
+        let target = source.with_file_name("target.png");
+        let mut target_png = testing_png();
+        target_png.remove_first_chunk("ruSt").unwrap();
+        write_png(&target, &target_png).unwrap();
+
+        InjectParams {
+            path: target.clone(),
+            chunk_file: chunk_file.clone(),
+            output_file: None,
+            force: true,
+        }
+        .process_command(Verbosity::Normal, DEFAULT_MAX_INPUT_SIZE)
+        .unwrap();
+
+        let injected = read_png(&target, DEFAULT_MAX_INPUT_SIZE).unwrap();
+        assert_eq!(injected.as_bytes(), testing_png().as_bytes());
+    }
+
+    #[test]
+    fn test_list_json() {
+        let (_dir, path) = testing_png_file("list_json");
+        let params = ListParams {
+            path: path.clone(),
+            describe: false,
+        };
+
+        let listing: serde_json::Value = serde_json::from_str(
+            &params
+                .process_command(true, ColorChoice::Always, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+        )
+        .unwrap();
+        let entries = listing.as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1]["index"], 1);
+        assert_eq!(entries[1]["type"], "ruSt");
+        assert_eq!(entries[1]["length"], 6);
+        assert_eq!(entries[1]["crc"], testing_png().chunks()[1].crc());
+
+        assert_eq!(
+            params
+                .process_command(false, ColorChoice::Never, false, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+            list_chunks(&testing_png(), false, ColorChoice::Never)
+        );
+    }
+
+    #[test]
+    fn test_count_json() {
+        let (_dir, path) = testing_png_file("count_json");
+        let mut params = CountParams {
+            path: path.clone(),
+            chunk_type: None,
+        };
+
+        let counts: BTreeMap<String, usize> = serde_json::from_str(
+            &params
+                .process_command(true, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts.values().sum::<usize>(), testing_png().len());
+
+        params.chunk_type = Some(String::from("IDAT"));
+        let counts: BTreeMap<String, usize> = serde_json::from_str(
+            &params
+                .process_command(true, DEFAULT_MAX_INPUT_SIZE)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(counts, BTreeMap::from([(String::from("IDAT"), 0)]));
+    }
+
+    #[test]
+    fn test_list_chunks() {
+        let listing = list_chunks(&testing_png(), false, ColorChoice::Never);
+        let lines: Vec<&str> = listing.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("0  IHDR  13  0x"));
+        assert!(lines[1].starts_with("1  ruSt  6  0x"));
+        assert_eq!(lines[2], "2  IEND  0  0xae426082");
+    }
+
+    #[test]
+    fn test_list_chunks_described() {
+        let listing = list_chunks(&testing_png(), true, ColorChoice::Never);
+        let lines: Vec<&str> = listing.lines().collect();
+
+        assert!(lines[0].ends_with("  image header"));
+        assert!(lines[1].ends_with(&format!("{:#010x}", testing_png().chunks()[1].crc())));
+        assert_eq!(lines[2], "2  IEND  0  0xae426082  image trailer");
+    }
+
+    #[test]
+    fn test_list_chunks_colored() {
+        let mut png = testing_png();
+        png.append_chunk(Chunk::new_with_crc(
+            ChunkType::from_str("baDc").unwrap(),
+            Vec::new(),
+            0,
+        ));
+        let listing = list_chunks(&png, false, ColorChoice::Always);
+        let lines: Vec<&str> = listing.lines().collect();
+
+        assert!(lines[0].starts_with("0  \x1b[32mIHDR\x1b[0m  13  0x"));
+        assert!(lines[1].starts_with("1  \x1b[36mruSt\x1b[0m  6  0x"));
+        assert_eq!(lines[3], "\x1b[31m3  baDc  0  0x00000000\x1b[0m");
+    }
+
+    #[test]
+    fn test_color_never_has_no_escapes() {
+        let cli = Cli::try_parse_from(["pngme", "--color", "never", "print", "x.png"]).unwrap();
+        assert_eq!(cli.color, ColorChoice::Never);
+        assert!(!cli.color.paint("IHDR", 32).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_color_always_has_escapes() {
+        let cli = Cli::try_parse_from(["pngme", "print", "x.png", "--color", "always"]).unwrap();
+        assert_eq!(cli.color, ColorChoice::Always);
+        assert_eq!(cli.color.paint("IHDR", 32), "\x1b[32mIHDR\x1b[0m");
+    }
+
+    #[test]
+    fn test_color_defaults_to_auto() {
+        let cli = Cli::try_parse_from(["pngme", "print", "x.png"]).unwrap();
+        assert_eq!(cli.color, ColorChoice::Auto);
+    }
+
+    #[test]
+    fn test_missing_file_is_readable_error() {
+        let params = DecodeParams {
+            path: PathBuf::from("does_not_exist.png"),
+            chunk_type: Some(String::from("RuSt")),
+            index: None,
+            last: false,
+            encoding: Encoding::Utf8,
+            output_file: None,
+            password: None,
+            text_only: false,
+        };
+
+        let err = params
+            .process_command(Verbosity::Normal, false, DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("could not read file 'does_not_exist.png'")
+        );
+    }
+
+    #[test]
+    fn test_read_input_over_max_size() {
+        let (_dir, path) = testing_png_file("max_size");
+        let len = fs::metadata(&path).unwrap().len();
+
+        assert_eq!(
+            read_input(&path, 16).unwrap_err().to_string(),
+            format!(
+                "file too large ({} bytes); pass --max-size to override",
+                len
+            )
+        );
+        assert_eq!(read_input(&path, len).unwrap().len() as u64, len);
+        let cli = Cli::try_parse_from(["pngme", "--max-size", "16", "list", "x.png"]).unwrap();
+        assert_eq!(cli.max_size, 16);
+    }
+
+    #[test]
+    fn test_verify_reports_bad_crc() {
+        let (_dir, path) = testing_png_file("verify");
+        let params = VerifyParams { path: path.clone() };
+        assert_eq!(
+            params.process_command(DEFAULT_MAX_INPUT_SIZE).unwrap(),
+            "OK: all 3 chunks are valid"
+        );
+
+        let mut bytes = fs::read(&path).unwrap();
+        let last_idx = bytes.len() - 1;
+        bytes[last_idx] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        let err = params
+            .process_command(DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("found 1 problem\n"));
+        assert!(err.contains("chunk 2 'IEND' has crc"));
 
-        format!("{}", png)
+        // the last crc byte of the ruSt chunk, just before the 12 byte IEND chunk
+        bytes[last_idx - 12] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+        let err = params
+            .process_command(DEFAULT_MAX_INPUT_SIZE)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("found 2 problems\n"));
     }
 }
@@ -1,12 +1,15 @@
 use crate::{
     chunk_type::ChunkType,
-    conversions::{bytes_to_u32, u8_to_string, u32_to_bytes},
+    conversions::{bytes_to_u32, u32_to_bytes},
 };
 use crc::{CRC_32_ISO_HDLC, Crc};
 use std::fmt;
 use std::fmt::{Debug, Display};
+use std::io::{self, Write};
+use std::str::FromStr;
 
 /// Represents a Chunk of an image
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Chunk {
     // length and crc are both 4 byte unsigned integers
     length: u32,
@@ -15,32 +18,103 @@ pub struct Chunk {
     crc: u32,
 }
 
+/// Represents an error encountered when parsing bytes into a Chunk
 #[derive(Debug, PartialEq, Eq)]
-pub struct ParseChunkError;
+pub enum ParseChunkError {
+    /// There are fewer bytes than a Chunk needs
+    TooShort,
+    /// The declared length is longer than Chunk::MAX_CHUNK_LENGTH
+    TooLong { declared: u32 },
+    /// The data given to build a Chunk is longer than Chunk::MAX_CHUNK_LENGTH
+    DataTooLong { actual: usize },
+    /// The declared length doesn't match how many data bytes there are
+    LengthMismatch { declared: u32, actual: u32 },
+    /// The ChunkType bytes aren't a valid ChunkType
+    InvalidChunkType,
+    /// A ChunkBuilder was built without ever being given a ChunkType
+    MissingChunkType,
+    /// A `type:message` string has no colon between the ChunkType and message
+    MissingSeparator,
+    /// The stored crc doesn't match the crc calculated from the ChunkType and data
+    CrcMismatch { expected: u32, actual: u32 },
+    /// The data starts with PayloadHeader::MAGIC but has a version or flags this
+    /// version of pngme doesn't know
+    UnsupportedPayloadHeader { version: u8, flags: u8 },
+}
+
+/// Allows a ParseChunkError to be displayed as a readable message
+impl Display for ParseChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseChunkError::TooShort => write!(f, "invalid chunk: not enough bytes"),
+            ParseChunkError::TooLong { declared } => write!(
+                f,
+                "invalid chunk: declared length {} is longer than {}",
+                declared,
+                Chunk::MAX_CHUNK_LENGTH
+            ),
+            ParseChunkError::DataTooLong { actual } => write!(
+                f,
+                "chunk data of {} bytes is longer than {}",
+                actual,
+                Chunk::MAX_CHUNK_LENGTH
+            ),
+            ParseChunkError::LengthMismatch { declared, actual } => write!(
+                f,
+                "invalid chunk: declared length {} but found {} bytes",
+                declared, actual
+            ),
+            ParseChunkError::InvalidChunkType => write!(f, "invalid chunk: bad chunk type"),
+            ParseChunkError::MissingChunkType => write!(f, "invalid chunk: no chunk type given"),
+            ParseChunkError::MissingSeparator => {
+                write!(f, "invalid chunk: expected 'type:message'")
+            }
+            ParseChunkError::CrcMismatch { expected, actual } => write!(
+                f,
+                "invalid chunk: expected crc {:#010x} but found {:#010x}",
+                expected, actual
+            ),
+            ParseChunkError::UnsupportedPayloadHeader { version, flags } => write!(
+                f,
+                "unsupported payload header: version {} with flags {:#04x}",
+                version, flags
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseChunkError {}
 
-/// Allows this Chunk to be made from a vec of bytes where:
+/// Allows this Chunk to be made from a list of bytes where:
 /// the first 4 bytes are length, next 4 are the ChunkType, the last 4 are the crc
 /// and the other bytes are the message in the chunk
-impl TryFrom<&Vec<u8>> for Chunk {
+impl TryFrom<&[u8]> for Chunk {
     type Error = ParseChunkError;
 
-    fn try_from(data: &Vec<u8>) -> Result<Self, Self::Error> {
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
         if data.len() < 12 {
-            return Err(ParseChunkError);
+            return Err(ParseChunkError::TooShort);
         }
         let length_bytes = [data[0], data[1], data[2], data[3]];
         let length = bytes_to_u32(length_bytes);
+        if length > Chunk::MAX_CHUNK_LENGTH {
+            return Err(ParseChunkError::TooLong { declared: length });
+        }
 
         let data_end_idx = data.len() - 4;
         let chunk_data_bytes = data[8..data_end_idx].to_vec();
 
         if chunk_data_bytes.len() as u32 != length {
-            return Err(ParseChunkError);
+            return Err(ParseChunkError::LengthMismatch {
+                declared: length,
+                actual: chunk_data_bytes.len() as u32,
+            });
         }
 
-        let chunk_type = ChunkType::try_from([data[4], data[5], data[6], data[7]]).unwrap();
+        let chunk_type = ChunkType::try_from([data[4], data[5], data[6], data[7]])
+            .map_err(|_| ParseChunkError::InvalidChunkType)?;
 
-        let calculated_crc = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(&data[4..data_end_idx]);
+        let calculated_crc = Chunk::compute_crc(&chunk_type, &chunk_data_bytes);
         let crc = bytes_to_u32([
             data[data_end_idx],
             data[data_end_idx + 1],
@@ -49,7 +123,10 @@ impl TryFrom<&Vec<u8>> for Chunk {
         ]);
 
         if calculated_crc != crc {
-            return Err(ParseChunkError);
+            return Err(ParseChunkError::CrcMismatch {
+                expected: calculated_crc,
+                actual: crc,
+            });
         }
 
         Ok(Chunk {
@@ -61,6 +138,118 @@ impl TryFrom<&Vec<u8>> for Chunk {
     }
 }
 
+/// Allows this Chunk to be made from a vec of bytes laid out the same as a slice
+impl TryFrom<&Vec<u8>> for Chunk {
+    type Error = ParseChunkError;
+
+    fn try_from(data: &Vec<u8>) -> Result<Self, Self::Error> {
+        Chunk::try_from(data.as_slice())
+    }
+}
+
+/// Allows this Chunk to be made from a `type:message` string, where everything before
+/// the first colon is the ChunkType and the rest is the message
+impl FromStr for Chunk {
+    type Err = ParseChunkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (chunk_type, message) = s.split_once(':').ok_or(ParseChunkError::MissingSeparator)?;
+
+        Chunk::from_strings(chunk_type, message)
+    }
+}
+
+/// Represents the kind of data a Chunk holds
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChunkPayload<'a> {
+    /// The data is valid UTF-8 text
+    Text(String),
+    /// The data isn't valid UTF-8, so it is kept as bytes
+    Binary(&'a [u8]),
+}
+
+/// Represents the header encode can put in front of chunk data to record which
+/// transformations were applied to it, so decode can reverse them on its own
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PayloadHeader {
+    /// The data was compressed with compress_marked
+    pub compressed: bool,
+    /// The data was encrypted with encrypt_message, after any compression
+    pub encrypted: bool,
+}
+
+/// Functions for building and reading a PayloadHeader
+impl PayloadHeader {
+    /// Marks the start of a PayloadHeader. It starts with a null byte so a plain
+    /// text message won't begin with it by accident
+    pub const MAGIC: [u8; 4] = *b"\0pmh";
+    /// The only header layout this version of pngme writes and reads
+    pub const VERSION: u8 = 1;
+    /// How many bytes a PayloadHeader takes: the magic, the version and the flags
+    pub const LEN: usize = 6;
+
+    const COMPRESSED_FLAG: u8 = 0b01;
+    const ENCRYPTED_FLAG: u8 = 0b10;
+
+    /// Returns this PayloadHeader as the bytes that go in front of the chunk data
+    pub fn to_bytes(self) -> [u8; PayloadHeader::LEN] {
+        let mut flags = 0;
+        if self.compressed {
+            flags |= PayloadHeader::COMPRESSED_FLAG;
+        }
+        if self.encrypted {
+            flags |= PayloadHeader::ENCRYPTED_FLAG;
+        }
+
+        let [m0, m1, m2, m3] = PayloadHeader::MAGIC;
+        [m0, m1, m2, m3, PayloadHeader::VERSION, flags]
+    }
+}
+
+/// Builds a Chunk one part at a time, starting from Chunk::builder
+#[derive(Debug, Clone, Default)]
+pub struct ChunkBuilder {
+    chunk_type: Option<ChunkType>,
+    data: Vec<u8>,
+    crc: Option<u32>,
+}
+
+/// Functions that set the parts of a ChunkBuilder and build the Chunk
+impl ChunkBuilder {
+    /// Sets the ChunkType of the Chunk from the given string.
+    /// If it isn't a valid ChunkType: return an error
+    pub fn chunk_type(mut self, chunk_type: &str) -> Result<ChunkBuilder, ParseChunkError> {
+        self.chunk_type =
+            Some(ChunkType::from_str(chunk_type).map_err(|_| ParseChunkError::InvalidChunkType)?);
+        Ok(self)
+    }
+
+    /// Sets the data of the Chunk
+    pub fn data(mut self, data: impl Into<Vec<u8>>) -> ChunkBuilder {
+        self.data = data.into();
+        self
+    }
+
+    /// Stores the given crc instead of the one calculated from the ChunkType and data,
+    /// such as to make a corrupt Chunk for testing
+    pub fn crc(mut self, crc: u32) -> ChunkBuilder {
+        self.crc = Some(crc);
+        self
+    }
+
+    /// Builds the Chunk, calculating its crc unless one was given.
+    /// If no ChunkType was set or the data is too long: return an error
+    pub fn build(self) -> Result<Chunk, ParseChunkError> {
+        let chunk_type = self.chunk_type.ok_or(ParseChunkError::MissingChunkType)?;
+        Chunk::check_data_len(self.data.len())?;
+
+        Ok(match self.crc {
+            Some(crc) => Chunk::new_with_crc(chunk_type, self.data, crc),
+            None => Chunk::new(chunk_type, self.data),
+        })
+    }
+}
+
 /// Allows this Chunk to be display in a string through formatting
 impl Display for Chunk {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -73,28 +262,130 @@ impl Display for Chunk {
 
 /// independent functions for Chunk
 impl Chunk {
+    /// The longest data a Chunk may hold, as set by the PNG spec
+    pub const MAX_CHUNK_LENGTH: u32 = (1 << 31) - 1;
+
     /// Creates a new Chunk object from the given ChunkType and data as bytes
     pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
-        let length = data.len() as u32;
+        let crc = Chunk::compute_crc(&chunk_type, &data);
 
-        let chunk_type_bytes = chunk_type.bytes();
-        let mut combined = data.clone();
+        Chunk::new_with_crc(chunk_type, data, crc)
+    }
+
+    /// Starts building a Chunk with no ChunkType, no data and a calculated crc
+    pub fn builder() -> ChunkBuilder {
+        ChunkBuilder::default()
+    }
+
+    /// Creates a new Chunk object from the given ChunkType and data as bytes.
+    /// If the data is longer than Chunk::MAX_CHUNK_LENGTH: return an error
+    pub fn try_new(chunk_type: ChunkType, data: Vec<u8>) -> Result<Chunk, ParseChunkError> {
+        Chunk::check_data_len(data.len())?;
+
+        Ok(Chunk::new(chunk_type, data))
+    }
 
-        combined.splice(0..0, chunk_type_bytes.iter().cloned());
+    /// Checks that data of the given length fits in a Chunk
+    fn check_data_len(len: usize) -> Result<(), ParseChunkError> {
+        if len > Chunk::MAX_CHUNK_LENGTH as usize {
+            return Err(ParseChunkError::DataTooLong { actual: len });
+        }
 
-        let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(&combined[0..combined.len()]);
+        Ok(())
+    }
 
+    /// Creates a new Chunk object from the given ChunkType and data as bytes, keeping
+    /// the given crc as is even if it doesn't match the ChunkType and data
+    pub fn new_with_crc(chunk_type: ChunkType, data: Vec<u8>, crc: u32) -> Chunk {
         Chunk {
-            length,
+            length: data.len() as u32,
             chunk_type,
             chunk_data_bytes: data,
             crc,
         }
     }
 
+    /// Calculates the crc over the bytes of the given ChunkType followed by the given data.
+    /// Every crc a Chunk is built or checked with comes from here
+    fn compute_crc(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+        let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+        let mut digest = crc.digest();
+        digest.update(&chunk_type.bytes());
+        digest.update(data);
+
+        digest.finalize()
+    }
+
+    /// Creates a new Chunk from the given chunk-type code and message.
+    /// If the chunk-type code isn't a valid ChunkType: return an error
+    pub fn from_strings(chunk_type: &str, message: &str) -> Result<Chunk, ParseChunkError> {
+        let chunk_type =
+            ChunkType::from_str(chunk_type).map_err(|_| ParseChunkError::InvalidChunkType)?;
+
+        Ok(Chunk::new(chunk_type, message.as_bytes().to_vec()))
+    }
+
+    /// Creates a new Chunk whose data holds each of the given fields, where every field
+    /// is a 4 byte big-endian length followed by that many bytes
+    pub fn new_framed(chunk_type: ChunkType, fields: &[&[u8]]) -> Chunk {
+        let mut data = Vec::<u8>::new();
+
+        // EFFECT: Adds each field's length and then its bytes to data
+        for field in fields {
+            data.extend_from_slice(&u32_to_bytes(field.len() as u32));
+            data.extend_from_slice(field);
+        }
+
+        Chunk::new(chunk_type, data)
+    }
+
+    /// Parses the data of this Chunk back into the fields it was framed with.
+    /// If a field is cut short: return an error
+    pub fn framed_fields(&self) -> Result<Vec<Vec<u8>>, ParseChunkError> {
+        let data = self.data();
+        let mut cur_idx = 0;
+        let mut fields = Vec::<Vec<u8>>::new();
+
+        // EFFECT: Reads fields until there is no more data left
+        while cur_idx < data.len() {
+            if data.len() - cur_idx < 4 {
+                return Err(ParseChunkError::TooShort);
+            }
+            let field_len = bytes_to_u32([
+                data[cur_idx],
+                data[cur_idx + 1],
+                data[cur_idx + 2],
+                data[cur_idx + 3],
+            ]) as usize;
+            cur_idx += 4;
+
+            if data.len() - cur_idx < field_len {
+                return Err(ParseChunkError::LengthMismatch {
+                    declared: field_len as u32,
+                    actual: (data.len() - cur_idx) as u32,
+                });
+            }
+            fields.push(data[cur_idx..cur_idx + field_len].to_vec());
+            cur_idx += field_len;
+        }
+
+        Ok(fields)
+    }
+
+    /// Changes the ChunkType of this Chunk, keeping its data and recomputing its crc
+    pub fn set_chunk_type(&mut self, chunk_type: ChunkType) {
+        *self = Chunk::new(chunk_type, std::mem::take(&mut self.chunk_data_bytes));
+    }
+
+    /// Replaces the stored crc of this Chunk with the one calculated from its
+    /// ChunkType and data
+    pub fn recompute_crc(&mut self) {
+        self.crc = Chunk::compute_crc(&self.chunk_type, &self.chunk_data_bytes);
+    }
+
     /// Returns the length of this Chunk
-    fn length(&self) -> u32 {
-        *&self.length
+    pub fn length(&self) -> u32 {
+        self.length
     }
 
     /// Returns a reference to this Chunk's ChunkType
@@ -103,24 +394,29 @@ impl Chunk {
     }
 
     /// Returns the data represented as bytes hidden in this Chunk
-    fn data(&self) -> &[u8] {
-        &self.chunk_data_bytes[0..*&self.chunk_data_bytes.len()]
+    pub fn data(&self) -> &[u8] {
+        &self.chunk_data_bytes[0..self.chunk_data_bytes.len()]
     }
 
     /// Returns the crc of this Chunk
-    fn crc(&self) -> u32 {
-        *&self.crc
+    pub fn crc(&self) -> u32 {
+        self.crc
     }
 
-    /// Returns the data represented as a String hidden in this Chunk
-    pub fn data_as_string(&self) -> Result<String, std::io::Error> {
-        let data = &self.data();
-        let mut data_str = String::new();
+    /// Returns the crc that this Chunk should hold given its ChunkType and data
+    pub fn expected_crc(&self) -> u32 {
+        Chunk::compute_crc(&self.chunk_type, &self.chunk_data_bytes)
+    }
 
-        // EFFECT:
-        for byte in data.iter() {
-            data_str += u8_to_string(*byte).as_str();
-        }
+    /// Determines if the crc of this Chunk matches its ChunkType and data
+    pub fn crc_is_valid(&self) -> bool {
+        self.crc == self.expected_crc()
+    }
+
+    /// Returns the data represented as a String hidden in this Chunk.
+    /// If the data isn't valid UTF-8, return an error
+    pub fn data_as_string(&self) -> Result<String, std::io::Error> {
+        let data = self.data();
 
         if self.length() != data.len() as u32 {
             return Err(std::io::Error::new(
@@ -129,23 +425,84 @@ impl Chunk {
             ));
         }
 
-        Ok(data_str)
+        String::from_utf8(data.to_vec()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Chunk data is not valid UTF-8",
+            )
+        })
+    }
+
+    /// Returns the data hidden in this Chunk as Text if it is valid UTF-8,
+    /// otherwise as Binary
+    pub fn payload(&self) -> ChunkPayload<'_> {
+        match std::str::from_utf8(self.data()) {
+            Ok(text) => ChunkPayload::Text(text.to_string()),
+            Err(_) => ChunkPayload::Binary(self.data()),
+        }
+    }
+
+    /// Splits the data of this Chunk into its PayloadHeader and the data after it.
+    /// Data that doesn't start with PayloadHeader::MAGIC has no header and is returned
+    /// whole. If the header is cut short or has an unknown version or flags: return an error
+    pub fn parse_payload_header(&self) -> Result<(Option<PayloadHeader>, &[u8]), ParseChunkError> {
+        let data = self.data();
+        if !data.starts_with(&PayloadHeader::MAGIC) {
+            return Ok((None, data));
+        }
+        if data.len() < PayloadHeader::LEN {
+            return Err(ParseChunkError::TooShort);
+        }
+
+        let (version, flags) = (data[4], data[5]);
+        let known_flags = PayloadHeader::COMPRESSED_FLAG | PayloadHeader::ENCRYPTED_FLAG;
+        if version != PayloadHeader::VERSION || flags & !known_flags != 0 {
+            return Err(ParseChunkError::UnsupportedPayloadHeader { version, flags });
+        }
+
+        let header = PayloadHeader {
+            compressed: flags & PayloadHeader::COMPRESSED_FLAG != 0,
+            encrypted: flags & PayloadHeader::ENCRYPTED_FLAG != 0,
+        };
+        Ok((Some(header), &data[PayloadHeader::LEN..]))
+    }
+
+    /// Returns the data represented as a String hidden in this Chunk, replacing
+    /// any bytes that aren't valid UTF-8 with the replacement character
+    pub fn data_as_string_lossy(&self) -> String {
+        String::from_utf8_lossy(self.data()).into_owned()
     }
 
     /// Returns this Chunk as a list of its bytes. Index 0 - 3 is the length,
     /// Index 4 - 7 is the Chunk type. Index 8 - 8 + length is the data
     /// and the last 4 indexes are the CRC
     pub fn as_bytes(&self) -> Vec<u8> {
-        let mut chunk_as_vec = Vec::<u8>::new();
+        let mut chunk_as_vec = Vec::<u8>::with_capacity(self.byte_len());
+        // writing into a Vec can't fail
+        self.write_to(&mut chunk_as_vec).unwrap();
 
-        chunk_as_vec.extend_from_slice(&u32_to_bytes(self.length));
-        chunk_as_vec.extend_from_slice(&self.chunk_type.bytes());
+        chunk_as_vec
+    }
 
-        let mut chunk_data = self.chunk_data_bytes.clone();
-        chunk_as_vec.append(&mut chunk_data);
-        chunk_as_vec.extend_from_slice(&u32_to_bytes(self.crc));
+    /// Returns how many bytes this Chunk takes up once serialized: 4 for the length,
+    /// 4 for the ChunkType, the data, and 4 for the crc
+    pub fn byte_len(&self) -> usize {
+        12 + self.length as usize
+    }
 
-        chunk_as_vec
+    /// Writes the bytes of this Chunk, laid out as in as_bytes, to the given writer
+    /// without building them into a Vec first. If the writer fails, return its error
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&u32_to_bytes(self.length))?;
+        writer.write_all(&self.chunk_type.bytes())?;
+        writer.write_all(&self.chunk_data_bytes)?;
+        writer.write_all(&u32_to_bytes(self.crc))
+    }
+
+    /// Determines if this Chunk holds the same ChunkType and data as the given Chunk.
+    /// Unlike `==`, the crc of both Chunks is ignored
+    pub fn content_eq(&self, other: &Chunk) -> bool {
+        self.chunk_type == other.chunk_type && self.chunk_data_bytes == other.chunk_data_bytes
     }
 }
 
@@ -170,7 +527,7 @@ mod tests {
             .copied()
             .collect();
 
-        Chunk::try_from(chunk_data.as_ref()).unwrap()
+        Chunk::try_from(&chunk_data).unwrap()
     }
 
     #[test]
@@ -226,7 +583,7 @@ mod tests {
             .copied()
             .collect();
 
-        let chunk = Chunk::try_from(chunk_data.as_ref()).unwrap();
+        let chunk = Chunk::try_from(&chunk_data).unwrap();
 
         let chunk_string = chunk.data_as_string().unwrap();
         let expected_chunk_string = String::from("This is where your secret message will be!");
@@ -253,9 +610,67 @@ mod tests {
             .copied()
             .collect();
 
-        let chunk = Chunk::try_from(chunk_data.as_ref());
+        let chunk = Chunk::try_from(&chunk_data);
+
+        assert!(matches!(
+            chunk,
+            Err(ParseChunkError::CrcMismatch {
+                actual: 2882656333,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_chunk_from_slice() {
+        let mut buffer = vec![0xaa; 3];
+        buffer.extend_from_slice(&testing_chunk().as_bytes());
+        buffer.extend_from_slice(&[0xbb; 5]);
+
+        let chunk = Chunk::try_from(&buffer[3..buffer.len() - 5]).unwrap();
+        assert_eq!(chunk, testing_chunk());
+    }
+
+    #[test]
+    fn test_short_chunk_from_bytes() {
+        let chunk_data = vec![0, 0, 0, 0, b'R', b'u', b'S'];
+        assert_eq!(Chunk::try_from(&chunk_data), Err(ParseChunkError::TooShort));
+    }
+
+    #[test]
+    fn test_length_mismatch_from_bytes() {
+        let mut chunk_data = testing_chunk().as_bytes();
+        chunk_data[3] += 1;
+
+        assert_eq!(
+            Chunk::try_from(&chunk_data),
+            Err(ParseChunkError::LengthMismatch {
+                declared: 43,
+                actual: 42
+            })
+        );
+    }
 
-        assert!(chunk.is_err());
+    #[test]
+    fn test_huge_length_from_bytes() {
+        let mut chunk_data = testing_chunk().as_bytes();
+        chunk_data[0..4].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        assert_eq!(
+            Chunk::try_from(&chunk_data),
+            Err(ParseChunkError::TooLong { declared: u32::MAX })
+        );
+    }
+
+    #[test]
+    fn test_invalid_chunk_type_from_bytes() {
+        let mut chunk_data = testing_chunk().as_bytes();
+        chunk_data[5] = b'1';
+
+        assert_eq!(
+            Chunk::try_from(&chunk_data),
+            Err(ParseChunkError::InvalidChunkType)
+        );
     }
 
     #[test]
@@ -274,7 +689,7 @@ mod tests {
             .copied()
             .collect();
 
-        let chunk: Chunk = TryFrom::try_from(chunk_data.as_ref()).unwrap();
+        let chunk: Chunk = TryFrom::try_from(&chunk_data).unwrap();
 
         let _chunk_string = format!("{}", chunk);
     }
@@ -295,7 +710,289 @@ mod tests {
             .copied()
             .collect();
 
-        let chunk: Chunk = TryFrom::try_from(chunk_data.as_ref()).unwrap();
+        let chunk: Chunk = TryFrom::try_from(&chunk_data).unwrap();
         assert_eq!(chunk_data, chunk.as_bytes());
     }
+
+    #[test]
+    fn test_new_crc_survives_round_trip() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, (0..=255).collect());
+
+        let parsed = Chunk::try_from(&chunk.as_bytes()).unwrap();
+        assert_eq!(parsed, chunk);
+        assert_eq!(
+            Chunk::compute_crc(parsed.chunk_type(), parsed.data()),
+            chunk.crc()
+        );
+    }
+
+    #[test]
+    fn test_byte_len_matches_as_bytes() {
+        let chunk = testing_chunk();
+        let bytes = chunk.as_bytes();
+
+        assert_eq!(chunk.byte_len(), 54);
+        assert_eq!(bytes.len(), chunk.byte_len());
+        assert_eq!(bytes.capacity(), chunk.byte_len());
+    }
+
+    #[test]
+    fn test_write_to_matches_as_bytes() {
+        let chunk = testing_chunk();
+        let mut cursor = io::Cursor::new(Vec::<u8>::new());
+        chunk.write_to(&mut cursor).unwrap();
+
+        assert_eq!(cursor.into_inner(), chunk.as_bytes());
+    }
+
+    #[test]
+    fn test_try_new() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let data = b"This is where your secret message will be!".to_vec();
+
+        assert_eq!(Chunk::try_new(chunk_type, data).unwrap(), testing_chunk());
+    }
+
+    #[test]
+    fn test_too_long_data_is_rejected() {
+        let max = Chunk::MAX_CHUNK_LENGTH as usize;
+
+        assert_eq!(Chunk::check_data_len(max), Ok(()));
+        assert_eq!(
+            Chunk::check_data_len(max + 1),
+            Err(ParseChunkError::DataTooLong { actual: max + 1 })
+        );
+        assert_eq!(
+            Chunk::check_data_len(u32::MAX as usize + 5),
+            Err(ParseChunkError::DataTooLong {
+                actual: u32::MAX as usize + 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_clone_equals_original() {
+        let chunk = testing_chunk();
+        let cloned = chunk.clone();
+        assert_eq!(cloned, chunk);
+
+        let different_crc =
+            Chunk::new_with_crc(chunk.chunk_type().clone(), chunk.data().to_vec(), 0);
+        assert_ne!(different_crc, chunk);
+    }
+
+    #[test]
+    fn test_content_eq_ignores_crc() {
+        let chunk = testing_chunk();
+        let stale_crc_chunk = Chunk {
+            crc: chunk.crc().wrapping_add(1),
+            ..testing_chunk()
+        };
+
+        assert!(chunk.content_eq(&stale_crc_chunk));
+        assert_ne!(chunk, stale_crc_chunk);
+    }
+
+    #[test]
+    fn test_new_with_crc_keeps_given_crc() {
+        let chunk = testing_chunk();
+        assert!(chunk.crc_is_valid());
+
+        let tampered = Chunk::new_with_crc(chunk.chunk_type().clone(), chunk.data().to_vec(), 1);
+        assert_eq!(tampered.crc(), 1);
+        assert_eq!(tampered.expected_crc(), chunk.crc());
+        assert!(!tampered.crc_is_valid());
+    }
+
+    #[test]
+    fn test_new_with_crc_is_rejected_when_parsed() {
+        let chunk = testing_chunk();
+        let tampered = Chunk::new_with_crc(chunk.chunk_type().clone(), chunk.data().to_vec(), 1);
+
+        assert_eq!(
+            Chunk::try_from(&tampered.as_bytes()),
+            Err(ParseChunkError::CrcMismatch {
+                expected: chunk.crc(),
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_strings() {
+        let chunk =
+            Chunk::from_strings("RuSt", "This is where your secret message will be!").unwrap();
+        assert_eq!(chunk, testing_chunk());
+    }
+
+    #[test]
+    fn test_chunk_from_str() {
+        let chunk = Chunk::from_str("RuSt:This is where your secret message will be!").unwrap();
+        assert_eq!(chunk, testing_chunk());
+
+        let chunk = Chunk::from_str("ruSt:a:b::c").unwrap();
+        assert_eq!(chunk.chunk_type().to_string(), "ruSt");
+        assert_eq!(chunk.data(), b"a:b::c");
+    }
+
+    #[test]
+    fn test_chunk_from_str_invalid() {
+        assert_eq!(
+            Chunk::from_str("ruSt hello"),
+            Err(ParseChunkError::MissingSeparator)
+        );
+        assert_eq!(
+            Chunk::from_str("ru5t:hello"),
+            Err(ParseChunkError::InvalidChunkType)
+        );
+        assert_eq!(
+            Chunk::from_str(":hello"),
+            Err(ParseChunkError::InvalidChunkType)
+        );
+    }
+
+    #[test]
+    fn test_from_strings_bad_chunk_type() {
+        assert_eq!(
+            Chunk::from_strings("ru", "Too short"),
+            Err(ParseChunkError::InvalidChunkType)
+        );
+    }
+
+    #[test]
+    fn test_framed_fields_round_trip() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let fields: [&[u8]; 3] = [b"name", b"", b"a much longer third field"];
+        let chunk = Chunk::new_framed(chunk_type, &fields);
+
+        let parsed = chunk.framed_fields().unwrap();
+        assert_eq!(parsed, fields.map(|field| field.to_vec()));
+    }
+
+    #[test]
+    fn test_truncated_framed_fields() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, vec![0, 0, 0, 9, 1, 2, 3]);
+
+        assert_eq!(
+            chunk.framed_fields(),
+            Err(ParseChunkError::LengthMismatch {
+                declared: 9,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_builder_calculates_crc() {
+        let chunk = Chunk::builder()
+            .chunk_type("RuSt")
+            .unwrap()
+            .data(b"This is where your secret message will be!".as_slice())
+            .build()
+            .unwrap();
+
+        assert_eq!(chunk, testing_chunk());
+        assert!(chunk.crc_is_valid());
+    }
+
+    #[test]
+    fn test_builder_overridden_crc() {
+        let chunk = Chunk::builder()
+            .chunk_type("RuSt")
+            .unwrap()
+            .data("hidden")
+            .crc(7)
+            .build()
+            .unwrap();
+
+        assert_eq!(chunk.crc(), 7);
+        assert!(!chunk.crc_is_valid());
+        assert_eq!(chunk.data(), b"hidden");
+        assert!(Chunk::builder().chunk_type("Ru1t").is_err());
+        assert_eq!(
+            Chunk::builder().data("no type").build(),
+            Err(ParseChunkError::MissingChunkType)
+        );
+    }
+
+    #[test]
+    fn test_payload_header_round_trip() {
+        let header = PayloadHeader {
+            compressed: true,
+            encrypted: true,
+        };
+        let mut data = header.to_bytes().to_vec();
+        data.extend_from_slice(b"body");
+        let chunk = Chunk::new(ChunkType::from_str("RuSt").unwrap(), data);
+
+        assert_eq!(
+            chunk.parse_payload_header(),
+            Ok((Some(header), b"body".as_slice()))
+        );
+    }
+
+    #[test]
+    fn test_payload_header_missing_or_unsupported() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let plain = Chunk::new(chunk_type.clone(), b"plain".to_vec());
+        assert_eq!(
+            plain.parse_payload_header(),
+            Ok((None, b"plain".as_slice()))
+        );
+
+        let mut data = PayloadHeader::MAGIC.to_vec();
+        let cut = Chunk::new(chunk_type.clone(), data.clone());
+        assert_eq!(cut.parse_payload_header(), Err(ParseChunkError::TooShort));
+
+        data.extend_from_slice(&[2, 0]);
+        let future = Chunk::new(chunk_type, data);
+        assert_eq!(
+            future.parse_payload_header(),
+            Err(ParseChunkError::UnsupportedPayloadHeader {
+                version: 2,
+                flags: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_utf8_string_round_trip() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let message = "Café crème 🦀";
+        let chunk = Chunk::new(chunk_type, message.as_bytes().to_vec());
+
+        assert_eq!(chunk.data_as_string().unwrap(), message);
+        assert_eq!(chunk.data_as_string_lossy(), message);
+    }
+
+    #[test]
+    fn test_binary_data_as_string() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, vec![b'h', b'i', 0xff, 0xfe]);
+
+        assert!(chunk.data_as_string().is_err());
+        assert_eq!(chunk.data_as_string_lossy(), "hi\u{fffd}\u{fffd}");
+    }
+
+    #[test]
+    fn test_text_payload() {
+        let chunk = testing_chunk();
+        assert_eq!(
+            chunk.payload(),
+            ChunkPayload::Text(String::from("This is where your secret message will be!"))
+        );
+    }
+
+    #[test]
+    fn test_binary_payload() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+        let chunk = Chunk::new(chunk_type, vec![b'h', b'i', 0xff, 0xfe]);
+
+        assert_eq!(
+            chunk.payload(),
+            ChunkPayload::Binary(&[b'h', b'i', 0xff, 0xfe])
+        );
+    }
 }
@@ -4,7 +4,7 @@ use std::fmt::{Debug, Display};
 use std::str::FromStr;
 
 /// Represents a ChunkType stored in every Chunk
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ChunkType {
     // arrays stored in each field are MSBit in idx 0 and LSBit in idx 7
     // byte_four represents the MSByte and byte_one represents the LSByte
@@ -14,10 +14,65 @@ pub struct ChunkType {
     byte_one: [bool; 8],
 }
 
+/// Holds the properties of a ChunkType described by the case of each of its letters
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkProperties {
+    pub critical: bool,
+    pub public: bool,
+    pub reserved_valid: bool,
+    pub safe_to_copy: bool,
+}
+
+/// Whether a ChunkType must be understood to display the image, set by the case of its 1st letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criticality {
+    Critical,
+    Ancillary,
+}
+
+/// Whether a ChunkType is defined by the PNG specification, set by the case of its 2nd letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
 /// Represents an error encountered when parsing an input for ChunkType
 #[derive(Debug, PartialEq, Eq)]
 pub struct ParseChunkTypeError;
 
+/// Allows a ParseChunkTypeError to be displayed as a readable message
+impl Display for ParseChunkTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid chunk type: must be 4 ASCII letters")
+    }
+}
+
+impl std::error::Error for ParseChunkTypeError {}
+
+/// Determines if the given byte may appear in a ChunkType, which only allows ASCII letters
+const fn is_chunk_type_byte(byte: u8) -> bool {
+    byte.is_ascii_alphabetic()
+}
+
+/// Determines if the given bytes would make a ChunkType, without constructing one.
+/// Only the characters are checked, not the reserved bit
+#[allow(dead_code)]
+pub const fn is_valid_bytes(bytes: [u8; 4]) -> bool {
+    let mut idx = 0;
+
+    // EFFECT: Checks each byte until one isn't allowed in a ChunkType
+    while idx < bytes.len() {
+        if !is_chunk_type_byte(bytes[idx]) {
+            return false;
+        }
+        idx += 1;
+    }
+
+    true
+}
+
 /// Allows the ChunkType to be made from an array of 4 u8 integers
 impl TryFrom<[u8; 4]> for ChunkType {
     type Error = ParseChunkTypeError;
@@ -31,7 +86,7 @@ impl TryFrom<[u8; 4]> for ChunkType {
             for idx in 0..4 {
                 let cur_num = nums[idx];
 
-                if (cur_num < 65) || (cur_num > 90 && cur_num < 97) || (cur_num > 122) {
+                if !is_chunk_type_byte(cur_num) {
                     return Err(ParseChunkTypeError);
                 }
 
@@ -53,16 +108,14 @@ impl FromStr for ChunkType {
     type Err = ParseChunkTypeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 4 {
+        if s.chars().count() != 4 || !s.chars().all(|my_char| my_char.is_ascii_alphabetic()) {
             Err(ParseChunkTypeError)
         } else {
-            let temp_str = String::from(s);
-            let temp_chars = temp_str.chars().enumerate();
             let mut bytes: [u8; 4] = [0, 0, 0, 0];
 
-            // EFFECT: Adds each string as an u8 to bytes
-            for (idx, my_char) in temp_chars {
-                bytes[idx] = char_to_u8(my_char);
+            // EFFECT: Adds each char as an u8 to bytes
+            for (idx, my_char) in s.chars().enumerate() {
+                bytes[idx] = char_to_u8(my_char).ok_or(ParseChunkTypeError)?;
             }
             ChunkType::try_from(bytes)
         }
@@ -72,14 +125,7 @@ impl FromStr for ChunkType {
 /// Allows a ChunkType to be displayed as a string
 impl Display for ChunkType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let ct_str = String::new();
-        let bytes = &self.bytes();
-
-        let ct_str = ct_str
-            + &u8_to_string(bytes[0])
-            + &u8_to_string(bytes[1])
-            + &u8_to_string(bytes[2])
-            + &u8_to_string(bytes[3]);
+        let ct_str: String = self.bytes().into_iter().map(byte_to_display_char).collect();
 
         write!(f, "{ct_str}")
     }
@@ -89,6 +135,71 @@ impl Display for ChunkType {
 /// Represents a ChunkType consisting of 4 bytes. Byte_four is the left and MSByte
 /// The bits in each byte are ordered from MSBit to LSBit
 impl ChunkType {
+    /// The ChunkType used when the user doesn't want to pick one themselves.
+    /// It is ancillary, private, reserved-bit valid, and safe-to-copy
+    pub const SAFE_PRIVATE: [u8; 4] = *b"ruSt";
+
+    /// Determines if this ChunkType is one defined by the PNG specification or its
+    /// registered extensions, which are exactly the ones with a standard_name
+    pub fn is_standard(&self) -> bool {
+        self.standard_name().is_some()
+    }
+
+    /// Returns a short description of this ChunkType if it is one defined by the PNG
+    /// specification or its registered extensions, such as "image header" for IHDR
+    pub fn standard_name(&self) -> Option<&'static str> {
+        let name = match &self.bytes() {
+            b"IHDR" => "image header",
+            b"PLTE" => "palette",
+            b"IDAT" => "image data",
+            b"IEND" => "image trailer",
+            b"bKGD" => "background color",
+            b"cHRM" => "primary chromaticities",
+            b"cICP" => "coding-independent code points",
+            b"cLLI" => "content light level information",
+            b"dSIG" => "digital signature",
+            b"eXIf" => "exif metadata",
+            b"gAMA" => "image gamma",
+            b"hIST" => "palette histogram",
+            b"iCCP" => "embedded ICC profile",
+            b"iTXt" => "international textual data",
+            b"mDCV" => "mastering display color volume",
+            b"pHYs" => "physical pixel dimensions",
+            b"sBIT" => "significant bits",
+            b"sPLT" => "suggested palette",
+            b"sRGB" => "standard RGB color space",
+            b"sTER" => "stereo image indicator",
+            b"tEXt" => "textual data",
+            b"tIME" => "last modification time",
+            b"tRNS" => "transparency",
+            b"zTXt" => "compressed textual data",
+            b"acTL" => "animation control",
+            b"fcTL" => "frame control",
+            b"fdAT" => "frame data",
+            _ => return None,
+        };
+
+        Some(name)
+    }
+
+    /// Creates the ChunkType that is safe to hide a message in without breaking the image
+    pub fn safe_private() -> ChunkType {
+        ChunkType::new_unchecked(ChunkType::SAFE_PRIVATE)
+    }
+
+    /// Creates a ChunkType from the given bytes, the same as ChunkType::try_from.
+    /// If any byte isn't an ASCII letter, return an error
+    pub fn new(bytes: [u8; 4]) -> Result<ChunkType, ParseChunkTypeError> {
+        ChunkType::try_from(bytes)
+    }
+
+    /// Creates a ChunkType from the given bytes WITHOUT checking that they are ASCII
+    /// letters. Only use it for bytes known to be valid, such as a literal like b"IEND";
+    /// otherwise the ChunkType won't survive being written and parsed again
+    pub fn new_unchecked(bytes: [u8; 4]) -> ChunkType {
+        ChunkType::from_arr_bytes(bytes.map(u8_to_bits))
+    }
+
     /// Creates a ChunkType from 4 bit representations of bytes. Where index0 is the MSB
     fn from_arr_bytes(bytes: [[bool; 8]; 4]) -> ChunkType {
         ChunkType {
@@ -109,42 +220,63 @@ impl ChunkType {
     }
 
     /// Determines if this ChunkType is valid; A ChunkType is valid if the
-    /// 2nd byte is an uppercase letter
-    fn is_valid(&self) -> bool {
+    /// 2nd byte is an uppercase letter. Any byte that isn't a letter is invalid
+    pub fn is_valid(&self) -> bool {
         let byte_three_num = bits_to_byte(&self.byte_two);
 
-        if (byte_three_num < 65)
-            || (byte_three_num > 90 && byte_three_num < 97)
-            || (byte_three_num > 122)
-        {
-            panic!("Invalid number given. Does not map to an ASCII letter");
-        }
-
-        !&self.byte_two[2]
+        is_chunk_type_byte(byte_three_num) && !self.byte_two[2]
     }
 
-    /// Determines if this ChunkType is critical (false) or ancillary (true)
+    /// Determines if this ChunkType is critical (true) or ancillary (false)
     /// A ChunkType is one or the other based on if the 4th byte is an uppercase letter
-    fn is_critical(&self) -> bool {
+    pub fn is_critical(&self) -> bool {
         !&self.byte_four[2]
     }
 
     /// Determines if this ChunkType is public.
     /// A ChunkType is public if the 3rd byte is a lowercase letter
-    fn is_public(&self) -> bool {
+    pub fn is_public(&self) -> bool {
         !&self.byte_three[2]
     }
 
     /// Determines if this ChunkType is reserved.
     /// A ChunkType is reserved if it is valid
-    fn is_reserved_bit_valid(&self) -> bool {
-        *&self.is_valid()
+    pub fn is_reserved_bit_valid(&self) -> bool {
+        self.is_valid()
     }
 
     /// Determines if this ChunkType is safe-to-copy.
     /// A ChunkType is safe-to-copy if the 1st byte is a lowercase letter
-    fn is_safe_to_copy(&self) -> bool {
-        *&self.byte_one[2]
+    pub fn is_safe_to_copy(&self) -> bool {
+        self.byte_one[2]
+    }
+
+    /// Returns whether this ChunkType is Critical or Ancillary
+    pub fn critical_state(&self) -> Criticality {
+        if self.is_critical() {
+            Criticality::Critical
+        } else {
+            Criticality::Ancillary
+        }
+    }
+
+    /// Returns whether this ChunkType is Public or Private
+    pub fn visibility(&self) -> Visibility {
+        if self.is_public() {
+            Visibility::Public
+        } else {
+            Visibility::Private
+        }
+    }
+
+    /// Returns the critical, public, reserved-bit, and safe-to-copy properties of this ChunkType
+    pub fn properties(&self) -> ChunkProperties {
+        ChunkProperties {
+            critical: self.is_critical(),
+            public: self.is_public(),
+            reserved_valid: self.is_reserved_bit_valid(),
+            safe_to_copy: self.is_safe_to_copy(),
+        }
     }
 }
 
@@ -152,6 +284,7 @@ impl ChunkType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::convert::TryFrom;
     use std::str::FromStr;
 
@@ -232,6 +365,124 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    pub fn test_non_ascii_chunk_type_is_err() {
+        assert_eq!(ChunkType::from_str("Ru§t"), Err(ParseChunkTypeError));
+        assert_eq!(ChunkType::from_str("R§t"), Err(ParseChunkTypeError));
+        assert_eq!(ChunkType::from_str("RuSé"), Err(ParseChunkTypeError));
+        assert_eq!(ChunkType::from_str("ÅÄÖÜ"), Err(ParseChunkTypeError));
+    }
+
+    #[test]
+    pub fn test_standard_name() {
+        let ihdr = ChunkType::from_str("IHDR").unwrap();
+        assert_eq!(ihdr.standard_name(), Some("image header"));
+        assert_eq!(ChunkType::from_str("ruSt").unwrap().standard_name(), None);
+        assert_eq!(ChunkType::from_str("Ihdr").unwrap().standard_name(), None);
+    }
+
+    #[test]
+    pub fn test_is_valid_bytes() {
+        const { assert!(is_valid_bytes(*b"RuSt")) };
+        assert!(is_valid_bytes(*b"IHDR"));
+        assert!(is_valid_bytes(*b"Rust"));
+        assert!(!is_valid_bytes(*b"Ru1t"));
+        assert!(!is_valid_bytes(*b"1234"));
+        assert!(!is_valid_bytes(*b"Ru-t"));
+        assert!(!is_valid_bytes(*b"[]@`"));
+        assert!(!is_valid_bytes([b'R', b'u', b'S', 0xc1]));
+    }
+
+    #[test]
+    pub fn test_is_valid_bytes_matches_try_from() {
+        // EFFECT: Checks both agree for every byte in every position
+        for byte in 0..=u8::MAX {
+            for idx in 0..4 {
+                let mut bytes = *b"RuSt";
+                bytes[idx] = byte;
+                assert_eq!(is_valid_bytes(bytes), ChunkType::try_from(bytes).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_safe_private_chunk_type() {
+        let chunk = ChunkType::safe_private();
+        assert!(!chunk.is_critical());
+        assert!(!chunk.is_public());
+        assert!(chunk.is_reserved_bit_valid());
+        assert!(chunk.is_safe_to_copy());
+    }
+
+    #[test]
+    pub fn test_chunk_type_critical_state() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.critical_state(), Criticality::Critical);
+        let chunk = ChunkType::from_str("ruSt").unwrap();
+        assert_eq!(chunk.critical_state(), Criticality::Ancillary);
+    }
+
+    #[test]
+    pub fn test_chunk_type_visibility() {
+        let chunk = ChunkType::from_str("RUSt").unwrap();
+        assert_eq!(chunk.visibility(), Visibility::Public);
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        assert_eq!(chunk.visibility(), Visibility::Private);
+    }
+
+    #[test]
+    pub fn test_chunk_type_properties() {
+        let chunk = ChunkType::from_str("RuSt").unwrap();
+        let properties = chunk.properties();
+        assert_eq!(properties.critical, chunk.is_critical());
+        assert_eq!(properties.public, chunk.is_public());
+        assert_eq!(properties.reserved_valid, chunk.is_reserved_bit_valid());
+        assert_eq!(properties.safe_to_copy, chunk.is_safe_to_copy());
+    }
+
+    #[test]
+    pub fn test_non_letter_reserved_byte_is_invalid() {
+        let chunk = ChunkType::from_arr_bytes([
+            u8_to_bits(b'R'),
+            u8_to_bits(b'u'),
+            u8_to_bits(b'1'),
+            u8_to_bits(b't'),
+        ]);
+        assert!(!chunk.is_valid());
+        assert!(!chunk.is_reserved_bit_valid());
+    }
+
+    #[test]
+    pub fn test_chunk_type_try_from_never_panics() {
+        // EFFECT: tries every byte in every position of an otherwise valid ChunkType
+        for idx in 0..4 {
+            for byte in 0..=u8::MAX {
+                let mut bytes = *b"RuSt";
+                bytes[idx] = byte;
+
+                let chunk = std::panic::catch_unwind(|| ChunkType::try_from(bytes));
+                assert!(chunk.is_ok(), "try_from panicked on {:?}", bytes);
+                assert_eq!(chunk.unwrap().is_ok(), byte.is_ascii_alphabetic());
+            }
+        }
+
+        // EFFECT: tries the same byte in every position
+        for byte in 0..=u8::MAX {
+            let chunk = ChunkType::try_from([byte; 4]);
+            assert_eq!(chunk.is_ok(), byte.is_ascii_alphabetic());
+        }
+    }
+
+    #[test]
+    pub fn test_chunk_type_is_standard() {
+        assert!(ChunkType::from_str("IHDR").unwrap().is_standard());
+        assert!(ChunkType::from_str("tEXt").unwrap().is_standard());
+        assert!(ChunkType::from_str("fcTL").unwrap().is_standard());
+        assert!(ChunkType::from_str("fdAT").unwrap().is_standard());
+        assert!(!ChunkType::from_str("Ihdr").unwrap().is_standard());
+        assert!(!ChunkType::from_str("ruSt").unwrap().is_standard());
+    }
+
     #[test]
     pub fn test_chunk_type_string() {
         let chunk = ChunkType::from_str("RuSt").unwrap();
@@ -245,4 +496,30 @@ mod tests {
         let _chunk_string = format!("{}", chunk_type_1);
         let _are_chunks_equal = chunk_type_1 == chunk_type_2;
     }
+
+    #[test]
+    pub fn test_chunk_type_new() {
+        let chunk_type = ChunkType::from_str("RuSt").unwrap();
+
+        assert_eq!(ChunkType::new_unchecked(*b"RuSt"), chunk_type);
+        assert_eq!(ChunkType::new(*b"RuSt"), Ok(chunk_type));
+        assert_eq!(ChunkType::new(*b"Ru1t"), Err(ParseChunkTypeError));
+    }
+
+    #[test]
+    pub fn test_chunk_type_as_map_key() {
+        let mut counts = HashMap::<ChunkType, usize>::new();
+        *counts
+            .entry(ChunkType::from_str("RuSt").unwrap())
+            .or_default() += 1;
+        *counts
+            .entry(ChunkType::try_from([82, 117, 83, 116]).unwrap())
+            .or_default() += 1;
+        *counts
+            .entry(ChunkType::from_str("IDAT").unwrap())
+            .or_default() += 1;
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&ChunkType::from_str("RuSt").unwrap()], 2);
+    }
 }
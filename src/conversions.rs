@@ -1,12 +1,25 @@
-// Converts the given u8 number into its string representation
-pub fn u8_to_string(num: u8) -> String {
-    (num as char).to_string()
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use std::io::{Read, Write};
+
+// Marks the start of data that was compressed with compress_marked. It starts with a
+//  null byte so a plain text message won't begin with it by accident
+pub const COMPRESSED_MARKER: [u8; 4] = *b"\0pmz";
+
+// Converts the given u8 number into a char that is safe to print to a terminal.
+//  Bytes that aren't printable ASCII are shown as '.'
+pub fn byte_to_display_char(num: u8) -> char {
+    if num.is_ascii_graphic() || num == b' ' {
+        num as char
+    } else {
+        '.'
+    }
 }
 
-// converts a single char to its ASCII representation
-pub fn char_to_u8(my_char: char) -> u8 {
-    // TODO find a better way to implement this
-    my_char as u8
+// converts a single char to its ASCII representation, or None if it isn't ASCII
+pub fn char_to_u8(my_char: char) -> Option<u8> {
+    my_char.is_ascii().then_some(my_char as u8)
 }
 
 // converts single u8 integer to its bit representation from MSB to LSB
@@ -30,34 +43,217 @@ pub fn u8_to_bits(num: u8) -> [bool; 8] {
 pub fn bits_to_byte(rep: &[bool; 8]) -> u8 {
     let mut sum = 0u8;
 
-    // EFFECT: Converts each bool to its binary representation and adds it to sum
+    // EFFECT: Converts each bool to its binary representation and sets it in sum
     for (idx, &bit) in rep.iter().enumerate() {
         if bit {
-            sum += 1u8 << (7 - idx);
+            sum |= 1u8 << (7 - idx);
         }
     }
 
     sum
 }
 
-// Converts 4 bytes to its u32 representation where index 0 is the MSB
+// Converts 4 bytes to its u32 representation where index 0 is the MSB (big-endian,
+//  as every PNG length and crc field is stored)
 pub fn bytes_to_u32(rep: [u8; 4]) -> u32 {
-    let mut sum = 0u32;
+    u32::from_be_bytes(rep)
+}
+
+// Converts u32 to its u8 representation where index 0 is the MSB (big-endian)
+pub fn u32_to_bytes(rep: u32) -> [u8; 4] {
+    rep.to_be_bytes()
+}
 
-    // EFFECT: Converts each u8 to its u32 representation and adds it to sum
-    for (idx, &byte) in rep.iter().enumerate() {
-        sum += (byte as u32) << (8 * (3 - idx));
+// Converts the given bytes to a string of lowercase hex digits, two per byte
+pub fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// Converts the given string of hex digits back to bytes, two digits per byte.
+//  If the string has an odd number of digits or a non-hex character, return None
+pub fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u32> = hex
+        .chars()
+        .map(|digit| digit.to_digit(16))
+        .collect::<Option<_>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return None;
     }
 
-    sum
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| (pair[0] << 4 | pair[1]) as u8)
+            .collect(),
+    )
 }
 
-// Converts u32 to its u8 representation where index 0 is the MSB
-pub fn u32_to_bytes(rep: u32) -> [u8; 4] {
-    [
-        ((rep >> 24) & 0xff) as u8,
-        ((rep >> 16) & 0xff) as u8,
-        ((rep >> 8) & 0xff) as u8,
-        (rep & 0xff) as u8,
-    ]
+// Converts the given bytes to standard base64 with '=' padding
+pub fn bytes_to_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    // EFFECT: Splits every 3 bytes into 4 groups of 6 bits and maps each to a character
+    for group in bytes.chunks(3) {
+        let sum = (group[0] as u32) << 16
+            | (*group.get(1).unwrap_or(&0) as u32) << 8
+            | *group.get(2).unwrap_or(&0) as u32;
+
+        for idx in 0..4 {
+            if idx <= group.len() {
+                encoded.push(ALPHABET[((sum >> (18 - 6 * idx)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+// Compresses the given bytes with zlib and puts COMPRESSED_MARKER in front of them
+pub fn compress_marked(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(COMPRESSED_MARKER.to_vec(), Compression::best());
+    // writing into a Vec can't fail
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+// Decompresses the given bytes if they start with COMPRESSED_MARKER, otherwise
+//  returns them as they are. If the marked bytes aren't valid zlib data, return an error
+pub fn decompress_marked(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    match bytes.strip_prefix(&COMPRESSED_MARKER) {
+        Some(compressed) => {
+            let mut decompressed = Vec::<u8>::new();
+            ZlibDecoder::new(compressed).read_to_end(&mut decompressed)?;
+            Ok(decompressed)
+        }
+        None => Ok(bytes.to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_to_u8() {
+        assert_eq!(char_to_u8('R'), Some(82));
+        assert_eq!(char_to_u8('§'), None);
+        assert_eq!(char_to_u8('é'), None);
+    }
+
+    #[test]
+    fn test_byte_round_trip() {
+        // EFFECT: Checks every byte survives the bit conversions, and that only ASCII
+        //  bytes survive as chars
+        for byte in 0u8..=255 {
+            assert_eq!(bits_to_byte(&u8_to_bits(byte)), byte);
+            assert_eq!(
+                bytes_to_u32([byte, 0, byte, 0]).to_be_bytes(),
+                [byte, 0, byte, 0]
+            );
+
+            let round_trip = char_to_u8(byte as char);
+            if byte.is_ascii() {
+                assert_eq!(round_trip, Some(byte));
+            } else {
+                assert_eq!(round_trip, None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bits_to_byte() {
+        assert_eq!(bits_to_byte(&[true; 8]), 255);
+        assert_eq!(bits_to_byte(&[false; 8]), 0);
+        assert_eq!(bits_to_byte(&u8_to_bits(82)), 82);
+    }
+
+    #[test]
+    fn test_bytes_to_u32() {
+        assert_eq!(bytes_to_u32([255, 255, 255, 255]), u32::MAX);
+        assert_eq!(bytes_to_u32([0, 0, 0, 0]), 0);
+        assert_eq!(bytes_to_u32([0, 0, 1, 2]), 258);
+    }
+
+    #[test]
+    fn test_u32_conversions_are_big_endian() {
+        // EFFECT: Checks both conversions against the standard big-endian ones
+        for num in [
+            0,
+            1,
+            255,
+            256,
+            0x1234_5678,
+            0x8000_0000,
+            u32::MAX - 1,
+            u32::MAX,
+        ] {
+            assert_eq!(bytes_to_u32(num.to_be_bytes()), num);
+            assert_eq!(u32_to_bytes(num), num.to_be_bytes());
+            assert_eq!(bytes_to_u32(u32_to_bytes(num)), num);
+        }
+        assert_eq!(u32_to_bytes(1), [0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_bytes_to_hex() {
+        assert_eq!(bytes_to_hex(&[]), "");
+        assert_eq!(bytes_to_hex(&[0, 15, 16, 171, 255]), "000f10abff");
+    }
+
+    #[test]
+    fn test_hex_to_bytes() {
+        assert_eq!(hex_to_bytes(""), Some(Vec::new()));
+        assert_eq!(hex_to_bytes("000f10ABff"), Some(vec![0, 15, 16, 171, 255]));
+        assert_eq!(hex_to_bytes("abc"), None);
+        assert_eq!(hex_to_bytes("zz"), None);
+        assert_eq!(hex_to_bytes("+f"), None);
+    }
+
+    #[test]
+    fn test_bytes_to_base64() {
+        assert_eq!(bytes_to_base64(b""), "");
+        assert_eq!(bytes_to_base64(b"f"), "Zg==");
+        assert_eq!(bytes_to_base64(b"fo"), "Zm8=");
+        assert_eq!(bytes_to_base64(b"foo"), "Zm9v");
+        assert_eq!(bytes_to_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(bytes_to_base64(&[0, 255, 254, 1]), "AP/+AQ==");
+    }
+
+    #[test]
+    fn test_compress_round_trip() {
+        let message = "hide me ".repeat(100);
+        let compressed = compress_marked(message.as_bytes());
+
+        assert!(compressed.starts_with(&COMPRESSED_MARKER));
+        assert!(compressed.len() < message.len());
+        assert_eq!(decompress_marked(&compressed).unwrap(), message.as_bytes());
+    }
+
+    #[test]
+    fn test_decompress_unmarked_is_unchanged() {
+        assert_eq!(decompress_marked(b"plain").unwrap(), b"plain");
+
+        let mut corrupt = COMPRESSED_MARKER.to_vec();
+        corrupt.extend_from_slice(b"not zlib");
+        assert!(decompress_marked(&corrupt).is_err());
+    }
+
+    #[test]
+    fn test_printable_bytes_display_as_themselves() {
+        assert_eq!(byte_to_display_char(b'R'), 'R');
+        assert_eq!(byte_to_display_char(b'~'), '~');
+        assert_eq!(byte_to_display_char(b' '), ' ');
+    }
+
+    #[test]
+    fn test_non_printable_bytes_display_as_dot() {
+        assert_eq!(byte_to_display_char(0), '.');
+        assert_eq!(byte_to_display_char(b'\n'), '.');
+        assert_eq!(byte_to_display_char(0x1b), '.');
+        assert_eq!(byte_to_display_char(0x7f), '.');
+        assert_eq!(byte_to_display_char(0xe9), '.');
+    }
 }
@@ -0,0 +1,129 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fmt::{self, Display};
+
+// Marks the start of data that was encrypted with encrypt_message. It starts with a
+//  null byte so a plain text message won't begin with it by accident
+pub const ENCRYPTED_MARKER: [u8; 4] = *b"\0pme";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Represents an error encountered when decrypting a message
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The data doesn't start with ENCRYPTED_MARKER
+    NotEncrypted,
+    /// The data is too short to hold the salt and nonce
+    Truncated,
+    /// The password is wrong or the data was changed after it was encrypted
+    AuthenticationFailed,
+}
+
+/// Allows a DecryptError to be displayed as a readable message
+impl Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecryptError::NotEncrypted => write!(f, "chunk data is not encrypted"),
+            DecryptError::Truncated => write!(f, "encrypted chunk data is truncated"),
+            DecryptError::AuthenticationFailed => {
+                write!(f, "could not decrypt: wrong password or tampered data")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+/// Determines if the given data was made by encrypt_message
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(&ENCRYPTED_MARKER)
+}
+
+/// Encrypts the given message with a key derived from the given password. The result holds
+/// ENCRYPTED_MARKER, then the salt and nonce needed to decrypt it, then the ciphertext
+pub fn encrypt_message(message: &[u8], password: &str) -> Vec<u8> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    // encrypting into a Vec only fails if the message is too long to count in a u64
+    let ciphertext = cipher_for(password, &salt)
+        .encrypt(&nonce, message)
+        .expect("message is too long to encrypt");
+
+    let mut data = ENCRYPTED_MARKER.to_vec();
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    data
+}
+
+/// Decrypts data made by encrypt_message with the given password.
+/// If the data isn't encrypted or the password is wrong: return an error
+pub fn decrypt_message(data: &[u8], password: &str) -> Result<Vec<u8>, DecryptError> {
+    let data = data
+        .strip_prefix(&ENCRYPTED_MARKER)
+        .ok_or(DecryptError::NotEncrypted)?;
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return Err(DecryptError::Truncated);
+    }
+
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    cipher_for(password, salt)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| DecryptError::AuthenticationFailed)
+}
+
+/// Derives a key from the given password and salt with Argon2 and builds a cipher from it
+fn cipher_for(password: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = Key::default();
+    // the salt and key lengths are always within Argon2's limits
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .expect("salt and key lengths are valid");
+
+    ChaCha20Poly1305::new(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let data = encrypt_message(b"meet me at noon", "hunter2");
+
+        assert!(is_encrypted(&data));
+        assert!(!data.windows(4).any(|window| window == b"noon"));
+        assert_eq!(
+            decrypt_message(&data, "hunter2").unwrap(),
+            b"meet me at noon"
+        );
+    }
+
+    #[test]
+    fn test_wrong_password() {
+        let data = encrypt_message(b"meet me at noon", "hunter2");
+
+        assert_eq!(
+            decrypt_message(&data, "hunter3"),
+            Err(DecryptError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_decrypt_unencrypted() {
+        assert_eq!(
+            decrypt_message(b"plain", "hunter2"),
+            Err(DecryptError::NotEncrypted)
+        );
+        assert_eq!(
+            decrypt_message(&ENCRYPTED_MARKER, "hunter2"),
+            Err(DecryptError::Truncated)
+        );
+    }
+}
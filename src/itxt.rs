@@ -0,0 +1,281 @@
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use flate2::read::ZlibDecoder;
+use std::fmt::{self, Display};
+use std::io::Read;
+
+/// The ChunkType of an international text Chunk
+pub const ITXT_TYPE: [u8; 4] = *b"iTXt";
+/// The longest keyword an iTXt Chunk may have, as set by the PNG spec
+const MAX_KEYWORD_LEN: usize = 79;
+
+/// Represents the fields stored in an iTXt Chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ITextEntry {
+    /// What the text is, such as "Title" or "Comment"
+    pub keyword: String,
+    /// The language the text is in, such as "en-US". Empty if it isn't known
+    pub language: String,
+    /// The keyword translated into the language
+    pub translated_keyword: String,
+    /// The text itself, already inflated if it was compressed
+    pub text: String,
+    /// Whether the text was stored compressed
+    pub compressed: bool,
+}
+
+/// Represents an error encountered when parsing a Chunk as an iTXt Chunk
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseITextError {
+    /// The Chunk's ChunkType isn't iTXt
+    NotITxt,
+    /// A field that must end in a null byte runs to the end of the data
+    MissingNull,
+    /// The keyword is empty, longer than MAX_KEYWORD_LEN bytes, or not Latin-1 text
+    /// without nulls
+    InvalidKeyword,
+    /// The language tag isn't ASCII text without nulls
+    InvalidLanguage,
+    /// The compression flag or method is one the PNG spec doesn't define
+    UnsupportedCompression { flag: u8, method: u8 },
+    /// The compressed text isn't valid zlib data
+    InvalidCompressedText,
+    /// The translated keyword or text isn't valid UTF-8
+    InvalidUtf8,
+}
+
+/// Allows a ParseITextError to be displayed as a readable message
+impl Display for ParseITextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseITextError::NotITxt => write!(f, "chunk is not an iTXt chunk"),
+            ParseITextError::MissingNull => {
+                write!(f, "invalid iTXt chunk: field is not terminated")
+            }
+            ParseITextError::InvalidKeyword => write!(
+                f,
+                "invalid iTXt chunk: keyword must be 1 to {} Latin-1 characters without nulls",
+                MAX_KEYWORD_LEN
+            ),
+            ParseITextError::InvalidLanguage => write!(
+                f,
+                "invalid iTXt chunk: language tag must be ASCII without nulls"
+            ),
+            ParseITextError::UnsupportedCompression { flag, method } => write!(
+                f,
+                "invalid iTXt chunk: unsupported compression flag {} method {}",
+                flag, method
+            ),
+            ParseITextError::InvalidCompressedText => {
+                write!(
+                    f,
+                    "invalid iTXt chunk: compressed text is not valid zlib data"
+                )
+            }
+            ParseITextError::InvalidUtf8 => {
+                write!(f, "invalid iTXt chunk: text is not valid UTF-8")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseITextError {}
+
+/// Functions for building and reading iTXt Chunks
+impl Chunk {
+    /// Creates an uncompressed iTXt Chunk holding the given text under the given keyword
+    /// and language tag. The translated keyword is left empty.
+    /// If the keyword isn't 1 to MAX_KEYWORD_LEN Latin-1 characters without nulls, or the
+    /// language tag isn't ASCII without nulls: return an error
+    pub fn new_itxt(keyword: &str, language: &str, text: &str) -> Result<Chunk, ParseITextError> {
+        // Latin-1 characters are the first 256 code points, each stored as a single byte
+        let keyword: Vec<u8> = keyword
+            .chars()
+            .map(|c| u8::try_from(c).ok().filter(|&byte| byte != 0))
+            .collect::<Option<_>>()
+            .ok_or(ParseITextError::InvalidKeyword)?;
+        if keyword.is_empty() || keyword.len() > MAX_KEYWORD_LEN {
+            return Err(ParseITextError::InvalidKeyword);
+        }
+        if !language.is_ascii() || language.contains('\0') {
+            return Err(ParseITextError::InvalidLanguage);
+        }
+
+        let mut data = keyword;
+        // the keyword's null, then no compression flag or method
+        data.extend_from_slice(&[0, 0, 0]);
+        data.extend_from_slice(language.as_bytes());
+        // the language tag's null, then the empty translated keyword's null
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(text.as_bytes());
+
+        Ok(Chunk::new(ChunkType::new_unchecked(ITXT_TYPE), data))
+    }
+
+    /// Parses the data of this iTXt Chunk into its fields, inflating the text if it was
+    /// compressed. If this isn't an iTXt Chunk or its data is malformed: return an error
+    pub fn parse_itxt(&self) -> Result<ITextEntry, ParseITextError> {
+        if self.chunk_type().bytes() != ITXT_TYPE {
+            return Err(ParseITextError::NotITxt);
+        }
+
+        let (keyword, rest) = split_at_null(self.data())?;
+        if keyword.is_empty() || keyword.len() > MAX_KEYWORD_LEN {
+            return Err(ParseITextError::InvalidKeyword);
+        }
+        let [flag, method, rest @ ..] = rest else {
+            return Err(ParseITextError::MissingNull);
+        };
+        let (language, rest) = split_at_null(rest)?;
+        if !language.is_ascii() {
+            return Err(ParseITextError::InvalidLanguage);
+        }
+        let (translated_keyword, text) = split_at_null(rest)?;
+
+        let text = match (flag, method) {
+            (0, 0) => text.to_vec(),
+            (1, 0) => {
+                let mut inflated = Vec::<u8>::new();
+                ZlibDecoder::new(text)
+                    .read_to_end(&mut inflated)
+                    .map_err(|_| ParseITextError::InvalidCompressedText)?;
+                inflated
+            }
+            _ => {
+                return Err(ParseITextError::UnsupportedCompression {
+                    flag: *flag,
+                    method: *method,
+                });
+            }
+        };
+
+        Ok(ITextEntry {
+            // the keyword is Latin-1, which maps byte for byte to chars, and the language
+            // tag is ASCII
+            keyword: keyword.iter().map(|&byte| byte as char).collect(),
+            language: language.iter().map(|&byte| byte as char).collect(),
+            translated_keyword: String::from_utf8(translated_keyword.to_vec())
+                .map_err(|_| ParseITextError::InvalidUtf8)?,
+            text: String::from_utf8(text).map_err(|_| ParseITextError::InvalidUtf8)?,
+            compressed: *flag == 1,
+        })
+    }
+}
+
+/// Splits the given bytes at the first null byte, dropping it.
+/// If there is no null byte, return an error
+fn split_at_null(bytes: &[u8]) -> Result<(&[u8], &[u8]), ParseITextError> {
+    let null_idx = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .ok_or(ParseITextError::MissingNull)?;
+
+    Ok((&bytes[..null_idx], &bytes[null_idx + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_itxt_round_trip() {
+        let chunk = Chunk::new_itxt("Comment", "en", "héllo from pngme").unwrap();
+
+        assert_eq!(chunk.chunk_type().to_string(), "iTXt");
+        assert_eq!(
+            chunk.parse_itxt(),
+            Ok(ITextEntry {
+                keyword: String::from("Comment"),
+                language: String::from("en"),
+                translated_keyword: String::new(),
+                text: String::from("héllo from pngme"),
+                compressed: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_compressed_itxt() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all("compressed text".as_bytes()).unwrap();
+        let mut data = b"Title\0\x01\x00de\0Titel\0".to_vec();
+        data.extend_from_slice(&encoder.finish().unwrap());
+        let chunk = Chunk::new(ChunkType::from_str("iTXt").unwrap(), data);
+
+        let entry = chunk.parse_itxt().unwrap();
+        assert_eq!(entry.keyword, "Title");
+        assert_eq!(entry.language, "de");
+        assert_eq!(entry.translated_keyword, "Titel");
+        assert_eq!(entry.text, "compressed text");
+        assert!(entry.compressed);
+    }
+
+    #[test]
+    fn test_parse_malformed_itxt() {
+        let itxt = |data: &[u8]| Chunk::new(ChunkType::from_str("iTXt").unwrap(), data.to_vec());
+
+        assert_eq!(
+            Chunk::from_strings("tEXt", "Title\0x")
+                .unwrap()
+                .parse_itxt(),
+            Err(ParseITextError::NotITxt)
+        );
+        assert_eq!(
+            itxt(b"no null").parse_itxt(),
+            Err(ParseITextError::MissingNull)
+        );
+        assert_eq!(
+            itxt(b"\0\0\0\0\0text").parse_itxt(),
+            Err(ParseITextError::InvalidKeyword)
+        );
+        assert_eq!(
+            itxt(b"Title\0\x00\x00\xe9n\0\0text").parse_itxt(),
+            Err(ParseITextError::InvalidLanguage)
+        );
+        assert_eq!(
+            itxt(b"Title\0\x01\x05\0\0text").parse_itxt(),
+            Err(ParseITextError::UnsupportedCompression { flag: 1, method: 5 })
+        );
+        assert_eq!(
+            itxt(b"Title\0\x00\x01\0\0text").parse_itxt(),
+            Err(ParseITextError::UnsupportedCompression { flag: 0, method: 1 })
+        );
+    }
+
+    #[test]
+    fn test_latin1_keyword_round_trip() {
+        let chunk = Chunk::new_itxt("Café", "", "text").unwrap();
+
+        assert!(chunk.data().starts_with(b"Caf\xe9\0"));
+        assert_eq!(chunk.parse_itxt().unwrap().keyword, "Café");
+    }
+
+    #[test]
+    fn test_new_itxt_rejects_what_parse_itxt_would() {
+        assert_eq!(
+            Chunk::new_itxt("", "en", "x"),
+            Err(ParseITextError::InvalidKeyword)
+        );
+        assert_eq!(
+            Chunk::new_itxt(&"k".repeat(MAX_KEYWORD_LEN + 1), "en", "x"),
+            Err(ParseITextError::InvalidKeyword)
+        );
+        assert_eq!(
+            Chunk::new_itxt("Ti\0tle", "en", "x"),
+            Err(ParseITextError::InvalidKeyword)
+        );
+        assert_eq!(
+            Chunk::new_itxt("Title €", "en", "x"),
+            Err(ParseITextError::InvalidKeyword)
+        );
+        assert_eq!(
+            Chunk::new_itxt("Title", "e\0n", "x"),
+            Err(ParseITextError::InvalidLanguage)
+        );
+        assert!(Chunk::new_itxt(&"k".repeat(MAX_KEYWORD_LEN), "en", "x").is_ok());
+    }
+}
@@ -5,15 +5,20 @@ mod chunk;
 mod chunk_type;
 mod commands;
 mod conversions;
+mod crypto;
+mod itxt;
 mod png;
+#[cfg(test)]
+mod testutil;
 
 pub type Error = Box<dyn std::error::Error>;
 pub type Result<T> = std::result::Result<T, Error>;
 
-fn main() -> Result<()> {
+fn main() {
     let cli = args::Cli::parse();
 
-    cli.process_command();
-
-    Ok(())
+    if let Err(err) = cli.process_command() {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
 }
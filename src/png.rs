@@ -1,71 +1,323 @@
-use crate::{chunk::Chunk, chunk_type::ChunkType, conversions::bytes_to_u32};
+use crate::{
+    chunk::{Chunk, ChunkPayload, ParseChunkError},
+    chunk_type::ChunkType,
+    conversions::bytes_to_u32,
+};
 use std::fmt::{Debug, Display};
+use std::io::{ErrorKind, Read, Write};
+use std::str::FromStr;
 
 /// Represents a PNG file by its Chunks
+#[derive(Debug, Clone)]
 pub struct Png {
     header: [u8; 8],
     chunks: Vec<Chunk>,
 }
 
+/// Represents an error encountered when removing, replacing, inserting, moving, or renaming a
+/// Chunk in a Png
 #[derive(Debug, PartialEq, Eq)]
-pub struct ChunkRemovalError;
+pub enum ChunkEditError {
+    /// There is no Chunk of the given ChunkType
+    NotFound,
+    /// The given index is past the last Chunk
+    IndexOutOfRange { index: usize, len: usize },
+    /// The Chunk is IHDR or IEND, which a valid Png can't do without
+    Protected(ChunkType),
+    /// The move would take IHDR off the front or IEND off the end
+    Pinned(ChunkType),
+    /// The rename would change a Chunk to or from IHDR or IEND
+    Unrenamable(ChunkType),
+}
+
+/// Allows a ChunkEditError to be displayed as a readable message
+impl Display for ChunkEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkEditError::NotFound => write!(f, "no chunk of the given type found"),
+            ChunkEditError::IndexOutOfRange { index, len } => write!(
+                f,
+                "no chunk at index {}: the file has {} chunks",
+                index, len
+            ),
+            ChunkEditError::Protected(chunk_type) => {
+                write!(f, "refusing to remove the {} chunk", chunk_type)
+            }
+            ChunkEditError::Pinned(chunk_type) => {
+                write!(
+                    f,
+                    "refusing to move the {} chunk from its place",
+                    chunk_type
+                )
+            }
+            ChunkEditError::Unrenamable(chunk_type) => {
+                write!(f, "refusing to rename a chunk to or from {}", chunk_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChunkEditError {}
+
+/// Represents an error encountered when adding a text Chunk to a Png
+#[derive(Debug, PartialEq, Eq)]
+pub enum AppendTextError {
+    /// The text Chunk couldn't be built
+    InvalidChunk(ParseChunkError),
+    /// The Png doesn't have the structure needed to insert before IEND
+    InvalidStructure(ParsePngError),
+}
+
+/// Allows an AppendTextError to be displayed as a readable message
+impl Display for AppendTextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppendTextError::InvalidChunk(source) => write!(f, "{}", source),
+            AppendTextError::InvalidStructure(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for AppendTextError {}
 
 impl Png {
     pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
 
-    /// Parses an arr of bytes to create Chunks.
-    /// If there is an Invalid Chunk: return an error,
-    /// If there are bytes leftover: return an error,
-    fn parse_for_chunks(bytes: &[u8]) -> Result<Vec<Chunk>, ParsePngError> {
+    /// Parses an arr of bytes that follow the signature to create Chunks using the
+    /// given ParseOptions.
+    /// If there is an Invalid Chunk: return an error with its offset in the file,
+    /// If there are bytes leftover that can't fit a Chunk: return an error,
+    /// If the Chunks break the limits in the options: return an error
+    fn parse_for_chunks(bytes: &[u8], options: &ParseOptions) -> Result<Vec<Chunk>, ParsePngError> {
         let num_bytes = bytes.len();
         let mut cur_idx = 0;
         let mut chunks = Vec::<Chunk>::new();
 
         // EFFECT: parse through all bytes until there are no more Chunks to be made
         while cur_idx < num_bytes {
+            let chunk_error = |source: ParseChunkError| ParsePngError::ChunkParse {
+                offset: Png::STANDARD_HEADER.len() + cur_idx,
+                source,
+            };
+            if num_bytes - cur_idx < 12 {
+                return Err(chunk_error(ParseChunkError::TooShort));
+            }
+
             let cur_chunk_len = bytes_to_u32([
                 bytes[cur_idx],
                 bytes[cur_idx + 1],
                 bytes[cur_idx + 2],
                 bytes[cur_idx + 3],
-            ]);
+            ]) as usize;
+            if num_bytes - cur_idx - 12 < cur_chunk_len {
+                return Err(chunk_error(ParseChunkError::LengthMismatch {
+                    declared: cur_chunk_len as u32,
+                    actual: (num_bytes - cur_idx - 12) as u32,
+                }));
+            }
+            if options
+                .max_chunk_data_len
+                .is_some_and(|max_len| cur_chunk_len > max_len as usize)
+            {
+                return Err(ParsePngError::ChunkTooLong {
+                    offset: Png::STANDARD_HEADER.len() + cur_idx,
+                });
+            }
+            if options
+                .max_chunks
+                .is_some_and(|max_chunks| chunks.len() >= max_chunks)
+            {
+                return Err(ParsePngError::TooManyChunks {
+                    offset: Png::STANDARD_HEADER.len() + cur_idx,
+                });
+            }
+
             let cur_chunk_type: ChunkType = ChunkType::try_from([
                 bytes[cur_idx + 4],
                 bytes[cur_idx + 5],
                 bytes[cur_idx + 6],
                 bytes[cur_idx + 7],
             ])
-            .unwrap();
-            let cur_data: Vec<u8> =
-                bytes[cur_idx + 8..cur_idx + 8 + cur_chunk_len as usize].to_vec();
+            .map_err(|_| chunk_error(ParseChunkError::InvalidChunkType))?;
+            let cur_chunk_bytes = &bytes[cur_idx..cur_idx + 12 + cur_chunk_len];
 
-            cur_idx += 12 + cur_chunk_len as usize;
-            chunks.push(Chunk::new(cur_chunk_type, cur_data));
-        }
+            let cur_chunk = match options.crc {
+                CrcHandling::Verify => Chunk::try_from(cur_chunk_bytes).map_err(chunk_error)?,
+                CrcHandling::Keep => Chunk::new_with_crc(
+                    cur_chunk_type,
+                    cur_chunk_bytes[8..8 + cur_chunk_len].to_vec(),
+                    bytes_to_u32([
+                        cur_chunk_bytes[8 + cur_chunk_len],
+                        cur_chunk_bytes[9 + cur_chunk_len],
+                        cur_chunk_bytes[10 + cur_chunk_len],
+                        cur_chunk_bytes[11 + cur_chunk_len],
+                    ]),
+                ),
+                CrcHandling::Recompute => Chunk::new(
+                    cur_chunk_type,
+                    cur_chunk_bytes[8..8 + cur_chunk_len].to_vec(),
+                ),
+            };
 
-        if num_bytes != cur_idx {
-            return Err(ParsePngError);
+            cur_idx += 12 + cur_chunk_len;
+            chunks.push(cur_chunk);
         }
 
         Ok(chunks)
     }
 
-    /// Creates a Png from the list of Chunks
-    fn from_chunks(chunks: Vec<Chunk>) -> Png {
+    /// Tries to create a Png from the list of bytes, parsing it as the given ParseOptions allow
+    pub fn try_from_with_options(
+        bytes: &[u8],
+        options: &ParseOptions,
+    ) -> Result<Png, ParsePngError> {
+        let header: [u8; 8] = match bytes.get(0..8).map(|header| header.try_into()) {
+            Some(Ok(header)) if header == Png::STANDARD_HEADER => header,
+            _ => return Err(ParsePngError::BadSignature),
+        };
+
+        let chunks: Vec<Chunk> = Png::parse_for_chunks(&bytes[8..bytes.len()], options)?;
+        Ok(Png { header, chunks })
+    }
+
+    /// Tries to create a Png from the list of bytes without verifying any crc, keeping
+    /// each Chunk's crc as stored so that mismatches can be found afterwards
+    pub fn try_from_lenient(bytes: &[u8]) -> Result<Png, ParsePngError> {
+        let options = ParseOptions {
+            crc: CrcHandling::Keep,
+            ..ParseOptions::default()
+        };
+
+        Png::try_from_with_options(bytes, &options)
+    }
+
+    /// Reads a Png from the given reader one Chunk at a time, stopping after the IEND Chunk.
+    /// If the reader fails or its bytes aren't a valid Png, return an error
+    pub fn from_reader<R: Read>(mut reader: R) -> crate::Result<Png> {
+        let mut header = [0u8; 8];
+        if reader.read_exact(&mut header).is_err() || header != Png::STANDARD_HEADER {
+            return Err(ParsePngError::BadSignature.into());
+        }
+
+        let mut chunks = Vec::<Chunk>::new();
+        let mut offset = Png::STANDARD_HEADER.len();
+
+        // EFFECT: reads Chunks until the IEND Chunk is read
+        loop {
+            let chunk_error =
+                |source: ParseChunkError| ParsePngError::ChunkParse { offset, source };
+            let mut length_bytes = [0u8; 4];
+            match reader.read_exact(&mut length_bytes) {
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                    return Err(ParsePngError::MissingIend.into());
+                }
+                result => result?,
+            }
+
+            // reading through take keeps a bad length from allocating more than the reader holds
+            let chunk_len = bytes_to_u32(length_bytes) as u64;
+            if chunk_len > Chunk::MAX_CHUNK_LENGTH as u64 {
+                return Err(chunk_error(ParseChunkError::TooLong {
+                    declared: chunk_len as u32,
+                })
+                .into());
+            }
+            let mut chunk_bytes = length_bytes.to_vec();
+            (&mut reader)
+                .take(chunk_len + 8)
+                .read_to_end(&mut chunk_bytes)?;
+
+            if chunk_bytes.len() < 12 {
+                return Err(chunk_error(ParseChunkError::TooShort).into());
+            }
+            if chunk_bytes.len() as u64 != chunk_len + 12 {
+                return Err(chunk_error(ParseChunkError::LengthMismatch {
+                    declared: chunk_len as u32,
+                    actual: (chunk_bytes.len() - 12) as u32,
+                })
+                .into());
+            }
+
+            let chunk = Chunk::try_from(&chunk_bytes).map_err(chunk_error)?;
+            let is_iend = chunk.chunk_type().to_string() == "IEND";
+            offset += chunk_bytes.len();
+            chunks.push(chunk);
+
+            if is_iend {
+                return Ok(Png { header, chunks });
+            }
+        }
+    }
+
+    /// Reads a Png from the start of the given bytes up to its IEND Chunk like from_reader.
+    /// Returns the Png and whatever bytes come after IEND
+    pub fn from_bytes_with_trailing(bytes: &[u8]) -> crate::Result<(Png, &[u8])> {
+        let png = Png::from_reader(bytes)?;
+        let trailing = &bytes[png.byte_len()..];
+
+        Ok((png, trailing))
+    }
+
+    /// Creates a Png holding only the standard signature and no Chunks.
+    /// It isn't a renderable PNG until IHDR, IDAT, and IEND Chunks are added
+    pub fn new() -> Png {
+        Png::from_chunks(Vec::new())
+    }
+
+    /// Creates a Png from the list of Chunks, in order, after the standard signature.
+    /// It is only a renderable PNG if the Chunks include proper IHDR, IDAT, and IEND Chunks
+    pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
         Png {
             header: Png::STANDARD_HEADER,
             chunks,
         }
     }
 
+    /// Creates an empty Png with room for the given number of Chunks
+    pub fn with_capacity(capacity: usize) -> Png {
+        Png {
+            header: Png::STANDARD_HEADER,
+            chunks: Vec::with_capacity(capacity),
+        }
+    }
+
     /// Appends the given Chunk to this Png
     pub fn append_chunk(&mut self, chunk: Chunk) {
         self.chunks.push(chunk);
     }
 
+    /// Inserts the given Chunk right before the IEND Chunk in this Png
+    /// If this Png doesn't pass validate_structure, return its error
+    pub fn insert_before_end(&mut self, chunk: Chunk) -> Result<(), ParsePngError> {
+        self.validate_structure()?;
+
+        // a valid structure always ends with the IEND Chunk
+        let iend_idx = self.chunks.len() - 1;
+        self.chunks.insert(iend_idx, chunk);
+        Ok(())
+    }
+
+    /// Builds a Chunk holding the given message with the given ChunkType and adds it
+    /// right before IEND with insert_before_end.
+    /// If the given chunk-type isn't valid or this Png doesn't pass validate_structure,
+    /// return an error
+    pub fn append_text(&mut self, type_code: &str, message: &str) -> Result<(), AppendTextError> {
+        let chunk =
+            Chunk::from_strings(type_code, message).map_err(AppendTextError::InvalidChunk)?;
+
+        self.insert_before_end(chunk)
+            .map_err(AppendTextError::InvalidStructure)
+    }
+
     /// Removes the first chunk in this Png that has the same ChunkType as the given ChunkType
-    /// If the given chunk-type doesn't exist in our png, return an error
-    pub fn remove_first_chunk(&mut self, chunk_type: &str) -> Result<Chunk, ChunkRemovalError> {
+    /// If the given chunk-type doesn't exist in our png or is IHDR or IEND, return an error
+    pub fn remove_first_chunk(&mut self, chunk_type: &str) -> Result<Chunk, ChunkEditError> {
+        if ["IHDR", "IEND"].contains(&chunk_type) {
+            // both names are valid ChunkTypes
+            let chunk_type = ChunkType::from_str(chunk_type).unwrap();
+            return Err(ChunkEditError::Protected(chunk_type));
+        }
+
         self.chunk_by_type(chunk_type)
             .and_then(|found| {
                 self.chunks()
@@ -73,7 +325,140 @@ impl Png {
                     .position(|chunk| std::ptr::eq(chunk, found))
             })
             .map(|idx| self.chunks.remove(idx))
-            .ok_or(ChunkRemovalError)
+            .ok_or(ChunkEditError::NotFound)
+    }
+
+    /// Removes every Chunk in this Png that has the same ChunkType as the given ChunkType.
+    /// Returns how many Chunks were removed. If the ChunkType is IHDR or IEND: return an error
+    pub fn remove_all_chunks_by_type(&mut self, chunk_type: &str) -> Result<usize, ChunkEditError> {
+        if ["IHDR", "IEND"].contains(&chunk_type) {
+            // both names are valid ChunkTypes
+            let chunk_type = ChunkType::from_str(chunk_type).unwrap();
+            return Err(ChunkEditError::Protected(chunk_type));
+        }
+
+        let len = self.len();
+        self.chunks
+            .retain(|chunk| chunk.chunk_type().to_string() != chunk_type);
+        Ok(len - self.len())
+    }
+
+    /// Removes the Chunk at the given index in this Png.
+    /// If there is no Chunk at the index or it is IHDR or IEND: return an error
+    pub fn remove_chunk_at(&mut self, index: usize) -> Result<Chunk, ChunkEditError> {
+        let chunk_type = self
+            .chunks()
+            .get(index)
+            .map(|chunk| chunk.chunk_type())
+            .ok_or(ChunkEditError::IndexOutOfRange {
+                index,
+                len: self.len(),
+            })?;
+
+        if ["IHDR", "IEND"].contains(&chunk_type.to_string().as_str()) {
+            return Err(ChunkEditError::Protected(chunk_type.clone()));
+        }
+
+        Ok(self.chunks.remove(index))
+    }
+
+    /// Moves the Chunk at index from so that it ends up at index to, shifting the Chunks
+    /// in between. If either index is out of range or the move would take IHDR off the
+    /// front or IEND off the end: return an error
+    pub fn move_chunk(&mut self, from: usize, to: usize) -> Result<(), ChunkEditError> {
+        let len = self.len();
+        if let Some(index) = [from, to].into_iter().find(|&index| index >= len) {
+            return Err(ChunkEditError::IndexOutOfRange { index, len });
+        }
+        if from == to {
+            return Ok(());
+        }
+
+        // EFFECT: checks that neither end holds a pinned Chunk that this move would displace
+        for (end_idx, name) in [(0, "IHDR"), (len - 1, "IEND")] {
+            let end_type = self.chunks[end_idx].chunk_type();
+            if (from == end_idx || to == end_idx) && end_type.to_string() == name {
+                return Err(ChunkEditError::Pinned(end_type.clone()));
+            }
+        }
+
+        let chunk = self.chunks.remove(from);
+        self.chunks.insert(to, chunk);
+        Ok(())
+    }
+
+    /// Inserts the given Chunk at the given index, shifting the Chunks from there on right.
+    /// If the index is past the end or the Chunk would come before IHDR or after IEND:
+    /// return an error
+    pub fn insert_chunk_at(&mut self, index: usize, chunk: Chunk) -> Result<(), ChunkEditError> {
+        let len = self.len();
+        if index > len {
+            return Err(ChunkEditError::IndexOutOfRange { index, len });
+        }
+
+        // IHDR must stay the first Chunk
+        if index == 0 {
+            // IHDR is a valid ChunkType
+            let ihdr = ChunkType::from_str("IHDR").unwrap();
+            return Err(ChunkEditError::Pinned(ihdr));
+        }
+        // nothing may come after IEND
+        if let Some(iend_idx) = self
+            .chunks()
+            .iter()
+            .position(|chunk| chunk.chunk_type().to_string() == "IEND")
+            && index > iend_idx
+        {
+            return Err(ChunkEditError::Pinned(
+                self.chunks[iend_idx].chunk_type().clone(),
+            ));
+        }
+
+        self.chunks.insert(index, chunk);
+        Ok(())
+    }
+
+    /// Changes the ChunkType of the Chunk at the given index, keeping its data and position
+    /// and recomputing its crc. If the index is out of range or either ChunkType is IHDR
+    /// or IEND: return an error
+    pub fn rename_chunk_type(
+        &mut self,
+        index: usize,
+        new_type: ChunkType,
+    ) -> Result<(), ChunkEditError> {
+        let len = self.len();
+        let chunk = self
+            .chunks
+            .get_mut(index)
+            .ok_or(ChunkEditError::IndexOutOfRange { index, len })?;
+
+        // EFFECT: checks that neither the old nor the new ChunkType is IHDR or IEND
+        for chunk_type in [chunk.chunk_type(), &new_type] {
+            if ["IHDR", "IEND"].contains(&chunk_type.to_string().as_str()) {
+                return Err(ChunkEditError::Unrenamable(chunk_type.clone()));
+            }
+        }
+
+        chunk.set_chunk_type(new_type);
+        Ok(())
+    }
+
+    /// Replaces the first chunk in this Png that has the same ChunkType as the given ChunkType
+    /// with the given Chunk, keeping its position.
+    /// If the given chunk-type doesn't exist in our png, return an error
+    pub fn replace_first_chunk(
+        &mut self,
+        chunk_type: &str,
+        chunk: Chunk,
+    ) -> Result<(), ChunkEditError> {
+        let found = self
+            .chunks_mut()
+            .iter_mut()
+            .find(|found| found.chunk_type().to_string() == chunk_type)
+            .ok_or(ChunkEditError::NotFound)?;
+
+        *found = chunk;
+        Ok(())
     }
 
     /// Returns the header of this Png. It should always be equal to the STANDARD_HEADER
@@ -82,10 +467,79 @@ impl Png {
     }
 
     /// Returns the chunks in this Png
-    fn chunks(&self) -> &[Chunk] {
+    pub fn chunks(&self) -> &[Chunk] {
         &self.chunks[0..self.chunks.len()]
     }
 
+    /// Returns an iterator over the Chunks in this Png, in order
+    pub fn iter(&self) -> std::slice::Iter<'_, Chunk> {
+        self.chunks.iter()
+    }
+
+    /// Returns how many Chunks are in this Png
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Determines if this Png has no Chunks
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns the chunks in this Png so they can be changed in place
+    pub fn chunks_mut(&mut self) -> &mut [Chunk] {
+        &mut self.chunks
+    }
+
+    /// Changes the ChunkType of every ancillary Chunk in this Png to the given ChunkType,
+    /// leaving critical Chunks alone. Returns how many Chunks were changed
+    pub fn retype_ancillary(&mut self, chunk_type: &ChunkType) -> usize {
+        let mut retyped = 0;
+
+        // EFFECT: retypes each ancillary Chunk and counts it
+        for chunk in self.chunks_mut() {
+            if !chunk.chunk_type().is_critical() {
+                chunk.set_chunk_type(chunk_type.clone());
+                retyped += 1;
+            }
+        }
+
+        retyped
+    }
+
+    /// Recalculates the crc of every Chunk in this Png from its ChunkType and data, so
+    /// as_bytes always writes valid checksums. Returns how many crcs were stale
+    pub fn recompute_crcs(&mut self) -> usize {
+        let mut fixed = 0;
+
+        // EFFECT: recomputes each stale crc and counts it
+        for chunk in self.chunks_mut() {
+            if !chunk.crc_is_valid() {
+                chunk.recompute_crc();
+                fixed += 1;
+            }
+        }
+
+        fixed
+    }
+
+    /// The standard ancillary ChunkTypes that hold text
+    pub const TEXT_CHUNK_TYPES: [&'static str; 3] = ["tEXt", "zTXt", "iTXt"];
+
+    /// Removes every ancillary Chunk in this Png, leaving critical Chunks alone. If
+    /// keep_text is set, Chunks of the TEXT_CHUNK_TYPES are kept too.
+    /// Returns how many Chunks were removed
+    pub fn strip_ancillary(&mut self, keep_text: bool) -> usize {
+        let len = self.len();
+        self.chunks.retain(|chunk| {
+            chunk.chunk_type().is_critical()
+                || (keep_text
+                    && Png::TEXT_CHUNK_TYPES.contains(&chunk.chunk_type().to_string().as_str()))
+        });
+
+        len - self.len()
+    }
+
     /// Finds the first Chunk in this Png that has the same ChunkType
     /// as the given ChunkType
     pub fn chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
@@ -94,58 +548,452 @@ impl Png {
             .find(|chunk| format!("{}", chunk.chunk_type()) == chunk_type)
     }
 
+    /// Returns the ChunkType of every Chunk in this Png, in order, as strings
+    pub fn chunk_types(&self) -> Vec<String> {
+        self.chunks()
+            .iter()
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect()
+    }
+
+    /// Determines if this Png has a Chunk with the given ChunkType
+    pub fn contains_chunk_type(&self, chunk_type: &str) -> bool {
+        self.chunk_by_type(chunk_type).is_some()
+    }
+
+    /// Finds the last Chunk in this Png that has the same ChunkType
+    /// as the given ChunkType
+    pub fn last_chunk_by_type(&self, chunk_type: &str) -> Option<&Chunk> {
+        self.chunks()
+            .iter()
+            .rfind(|chunk| chunk.chunk_type().to_string() == chunk_type)
+    }
+
+    /// Returns the Chunk at the given index in this Png, if there is one
+    pub fn chunk_by_index(&self, index: usize) -> Option<&Chunk> {
+        self.chunks.get(index)
+    }
+
+    /// Returns the size in bytes of the uncompressed image described by the IHDR Chunk,
+    /// which is the width × height × bytes-per-pixel. If there is no valid IHDR, return None
+    pub fn raw_image_size(&self) -> Option<u64> {
+        let ihdr = self.chunk_by_type("IHDR")?.data();
+        if ihdr.len() != 13 {
+            return None;
+        }
+
+        let width = bytes_to_u32([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]) as u64;
+        let height = bytes_to_u32([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]) as u64;
+        let bit_depth = ihdr[8] as u64;
+        let samples_per_pixel = match ihdr[9] {
+            0 | 3 => 1,
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => return None,
+        };
+
+        // rows are padded to a whole byte when pixels are smaller than a byte
+        let bytes_per_row = (width * samples_per_pixel * bit_depth).div_ceil(8);
+        Some(bytes_per_row * height)
+    }
+
+    /// Returns the total number of compressed image bytes in the IDAT Chunks
+    pub fn idat_size(&self) -> u64 {
+        self.chunks()
+            .iter()
+            .filter(|chunk| chunk.chunk_type().to_string() == "IDAT")
+            .map(|chunk| chunk.length() as u64)
+            .sum()
+    }
+
+    /// Returns the sum of the data lengths of every Chunk in this Png
+    pub fn total_data_size(&self) -> u64 {
+        self.chunks()
+            .iter()
+            .map(|chunk| chunk.length() as u64)
+            .sum()
+    }
+
+    /// Returns how large the compressed image data is compared to the raw image.
+    /// If the raw image size can't be found or is empty, return None
+    pub fn compression_ratio(&self) -> Option<f64> {
+        match self.raw_image_size()? {
+            0 => None,
+            raw_size => Some(self.idat_size() as f64 / raw_size as f64),
+        }
+    }
+
+    /// Private Chunks holding more data than this are suspicious
+    pub const LARGE_PRIVATE_CHUNK: u32 = 1024;
+
+    /// Looks for signs that data has been hidden in this Png: Chunks with a ChunkType
+    /// that isn't standard, Chunks after IEND, and large private Chunks.
+    /// Returns a description of every sign found
+    pub fn hidden_data_signs(&self) -> Vec<String> {
+        let mut signs = Vec::<String>::new();
+        let mut after_iend = 0;
+        let mut seen_iend = false;
+
+        // EFFECT: checks each Chunk for every sign of hidden data
+        for (idx, chunk) in self.chunks().iter().enumerate() {
+            let chunk_type = chunk.chunk_type();
+
+            if !chunk_type.is_standard() {
+                signs.push(format!(
+                    "non-standard chunk '{}' at index {}",
+                    chunk_type, idx
+                ));
+            }
+            if !chunk_type.is_public() && chunk.length() > Png::LARGE_PRIVATE_CHUNK {
+                signs.push(format!(
+                    "large private chunk '{}' ({} bytes) at index {}",
+                    chunk_type,
+                    chunk.length(),
+                    idx
+                ));
+            }
+            if seen_iend {
+                after_iend += 1;
+            }
+            seen_iend = seen_iend || chunk_type.to_string() == "IEND";
+        }
+
+        if after_iend > 0 {
+            signs.push(format!("{} chunks after IEND", after_iend));
+        }
+
+        signs
+    }
+
+    /// Checks that this Png starts with its only IHDR Chunk and ends with its only IEND
+    /// Chunk, with nothing after IEND.
+    /// If any of these doesn't hold: return an error for the first one that fails
+    pub fn validate_structure(&self) -> Result<(), ParsePngError> {
+        match self.structure_problems().into_iter().next() {
+            Some(problem) => Err(problem),
+            None => Ok(()),
+        }
+    }
+
+    /// Finds every way this Png breaks the IHDR-first and IEND-last rules
+    fn structure_problems(&self) -> Vec<ParsePngError> {
+        let mut problems = Vec::<ParsePngError>::new();
+        let positions = |name: &str| -> Vec<usize> {
+            self.chunks()
+                .iter()
+                .enumerate()
+                .filter(|(_, chunk)| chunk.chunk_type().to_string() == name)
+                .map(|(idx, _)| idx)
+                .collect()
+        };
+
+        let ihdr_positions = positions("IHDR");
+        match ihdr_positions.first() {
+            None => problems.push(ParsePngError::MissingIhdr),
+            Some(&index) if index != 0 => problems.push(ParsePngError::IhdrNotFirst { index }),
+            Some(_) => {}
+        }
+
+        let iend_positions = positions("IEND");
+        match iend_positions.first() {
+            None => problems.push(ParsePngError::MissingIend),
+            Some(&iend_idx) => {
+                let after_iend = self.chunks()[iend_idx..]
+                    .iter()
+                    .position(|chunk| chunk.chunk_type().to_string() != "IEND");
+                if let Some(offset) = after_iend {
+                    problems.push(ParsePngError::ChunkAfterIend {
+                        index: iend_idx + offset,
+                    });
+                }
+            }
+        }
+
+        // EFFECT: reports an IHDR or IEND Chunk that appears more than once
+        for (name, found) in [("IHDR", ihdr_positions), ("IEND", iend_positions)] {
+            if found.len() > 1 {
+                problems.push(ParsePngError::RepeatedChunk {
+                    chunk_type: ChunkType::from_str(name).unwrap(),
+                    count: found.len(),
+                });
+            }
+        }
+
+        problems
+    }
+
+    /// Finds every structural problem in this Png: Chunks whose crc doesn't match their
+    /// ChunkType and data, and every problem found by validate_structure
+    pub fn integrity_problems(&self) -> Vec<String> {
+        let mut problems = Vec::<String>::new();
+
+        // EFFECT: checks the crc of each Chunk
+        for (idx, chunk) in self.chunks().iter().enumerate() {
+            if !chunk.crc_is_valid() {
+                problems.push(format!(
+                    "chunk {} '{}' has crc {:#010x} but expected {:#010x}",
+                    idx,
+                    chunk.chunk_type(),
+                    chunk.crc(),
+                    chunk.expected_crc()
+                ));
+            }
+        }
+
+        problems.extend(
+            self.structure_problems()
+                .iter()
+                .map(|problem| problem.to_string()),
+        );
+
+        problems
+    }
+
     /// Converts this Png into a Vec of bytes
     pub fn as_bytes(&self) -> Vec<u8> {
-        let png_vec = self.header().to_vec();
+        let mut png_vec = Vec::<u8>::with_capacity(self.byte_len());
+        // writing into a Vec can't fail
+        self.write_to(&mut png_vec).unwrap();
+
+        png_vec
+    }
+
+    /// Writes the signature and then each Chunk of this Png in order to the given writer,
+    /// without building the whole file into a Vec first. If the writer fails, return its error
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.header())?;
+
+        // EFFECT: Writes the bytes of each Chunk in order
+        for chunk in self.chunks() {
+            chunk.write_to(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns how many bytes this Png takes up once serialized: the header followed by
+    /// every Chunk
+    pub fn byte_len(&self) -> usize {
+        self.header().len() + self.chunks().iter().map(Chunk::byte_len).sum::<usize>()
+    }
+}
+
+/// Decides what parsing does with the crc stored in each Chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrcHandling {
+    /// Rejects any Chunk whose crc doesn't match its ChunkType and data
+    Verify,
+    /// Keeps each Chunk's stored crc, even a wrong one, so it can be checked afterwards
+    Keep,
+    /// Replaces each Chunk's stored crc with the one computed from its ChunkType and data
+    Recompute,
+}
+
+/// Controls how strictly bytes are parsed into a Png
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// What to do with the crc stored in each Chunk
+    pub crc: CrcHandling,
+    /// The most Chunks a Png may hold, if limited
+    pub max_chunks: Option<usize>,
+    /// The most data bytes a single Chunk may hold, if limited
+    pub max_chunk_data_len: Option<u32>,
+}
 
-        self.chunks().iter().fold(png_vec, |mut accum_vec, chunk| {
-            accum_vec.extend_from_slice(&chunk.as_bytes());
-            accum_vec
-        })
+/// By default every crc is verified and there are no limits
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            crc: CrcHandling::Verify,
+            max_chunks: None,
+            max_chunk_data_len: None,
+        }
     }
 }
 
+/// Represents an error encountered when parsing bytes into a Png
 #[derive(Debug, PartialEq, Eq)]
-pub struct ParsePngError;
+pub enum ParsePngError {
+    /// The bytes don't start with the standard PNG signature
+    BadSignature,
+    /// The Chunk starting at the given byte offset in the file couldn't be parsed
+    ChunkParse {
+        offset: usize,
+        source: ParseChunkError,
+    },
+    /// The Chunk starting at the given byte offset is one more than the ParseOptions allow
+    TooManyChunks { offset: usize },
+    /// The Chunk starting at the given byte offset holds more data than the ParseOptions allow
+    ChunkTooLong { offset: usize },
+    /// There is no IEND Chunk to end the Png
+    MissingIend,
+    /// There is no IHDR Chunk to start the Png
+    MissingIhdr,
+    /// The IHDR Chunk is at the given index instead of first
+    IhdrNotFirst { index: usize },
+    /// The Chunk at the given index comes after the IEND Chunk
+    ChunkAfterIend { index: usize },
+    /// A Chunk that may appear only once appears the given number of times
+    RepeatedChunk { chunk_type: ChunkType, count: usize },
+}
+
+/// Allows a ParsePngError to be displayed as a readable message
+impl Display for ParsePngError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParsePngError::BadSignature => write!(f, "not a PNG file: signature mismatch"),
+            ParsePngError::ChunkParse { offset, source } => {
+                write!(f, "failed at offset {} parsing chunk: {}", offset, source)
+            }
+            ParsePngError::TooManyChunks { offset } => write!(
+                f,
+                "PNG file has too many chunks: limit reached at offset {}",
+                offset
+            ),
+            ParsePngError::ChunkTooLong { offset } => {
+                write!(
+                    f,
+                    "PNG file has a chunk that is too long at offset {}",
+                    offset
+                )
+            }
+            ParsePngError::MissingIend => write!(f, "PNG file has no IEND chunk"),
+            ParsePngError::MissingIhdr => write!(f, "PNG file has no IHDR chunk"),
+            ParsePngError::IhdrNotFirst { index } => write!(
+                f,
+                "PNG file has its IHDR chunk at index {} instead of first",
+                index
+            ),
+            ParsePngError::ChunkAfterIend { index } => {
+                write!(f, "PNG file has a chunk after IEND at index {}", index)
+            }
+            ParsePngError::RepeatedChunk { chunk_type, count } => write!(
+                f,
+                "PNG file has {} {} chunks, expected 1",
+                count, chunk_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParsePngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParsePngError::ChunkParse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// By default a Png is empty, the same as Png::new
+impl Default for Png {
+    fn default() -> Self {
+        Png::new()
+    }
+}
 
 /// Tries to create a Png from the list of bytes
 impl TryFrom<&[u8]> for Png {
     type Error = ParsePngError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        if bytes.len() < 9 || &bytes[0..8] != Png::STANDARD_HEADER {
-            return Err(ParsePngError);
-        }
+        Png::try_from_with_options(bytes, &ParseOptions::default())
+    }
+}
 
-        let header = match bytes[0..8].try_into() {
-            Ok(header) => header,
-            Err(_) => return Err(ParsePngError),
-        };
+/// Tries to create a Png from an owned list of bytes. This is only a convenience over the
+/// slice impl for callers that hold a Vec: each Chunk still copies its data out of the bytes
+impl TryFrom<Vec<u8>> for Png {
+    type Error = ParsePngError;
 
-        let chunks: Vec<Chunk> = Png::parse_for_chunks(&bytes[8..bytes.len()])?;
-        Ok(Png { header, chunks })
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Png::try_from(bytes.as_slice())
     }
 }
 
-/// Displays this Png in a formatted manner
+/// Allows iterating over the Chunks of a borrowed Png in a for loop
+impl<'a> IntoIterator for &'a Png {
+    type Item = &'a Chunk;
+    type IntoIter = std::slice::Iter<'a, Chunk>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Displays a summary of this Png: its signature and then one line per Chunk with its
+/// index, ChunkType, length, and a short preview of its message
 impl Display for Png {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut png_str = String::new();
+        self.write_summary(f, &[])
+    }
+}
 
-        // EFFECT: Combines all messages hidden in the Chunks of this Png
-        // into one string
-        for chunk in self.chunks() {
-            match chunk.data_as_string() {
-                Ok(str) => {
-                    png_str.push_str(&str);
-                    png_str.push_str("\n");
-                }
-                Err(_) => panic!("There was an error formatting this png"),
-            };
+/// Functions that summarize a Png for printing
+impl Png {
+    /// Summarizes this Png like its Display does, but only lists the Chunks whose
+    /// ChunkType is one of the given types. If no types are given, every Chunk is listed
+    pub fn summary_of_types(&self, types: &[String]) -> String {
+        let mut summary = String::new();
+        // writing into a String can't fail
+        self.write_summary(&mut summary, types).unwrap();
+        summary
+    }
+
+    /// Writes the signature and a summary line for each Chunk with one of the given
+    /// types (or every Chunk if there are none) to the given writer
+    fn write_summary<W: std::fmt::Write>(&self, f: &mut W, types: &[String]) -> std::fmt::Result {
+        let signature: Vec<String> = self
+            .header()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        write!(f, "Signature: {}", signature.join(" "))?;
+
+        // EFFECT: Adds a summary line for each Chunk that passes the filter, keeping
+        //  its index in the whole file
+        for (idx, chunk) in self.chunks().iter().enumerate() {
+            if !types.is_empty() && !types.contains(&chunk.chunk_type().to_string()) {
+                continue;
+            }
+            write!(
+                f,
+                "\n{}  {}  {}  {}",
+                idx,
+                chunk.chunk_type(),
+                chunk.length(),
+                preview_message(chunk)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The most characters of a message shown when previewing a Chunk
+const PREVIEW_LENGTH: usize = 40;
+
+/// Previews the message in the given Chunk in quotes, cut to PREVIEW_LENGTH characters.
+/// If the data doesn't look like text, only its size is shown
+fn preview_message(chunk: &Chunk) -> String {
+    let text = match chunk.payload() {
+        ChunkPayload::Text(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+            text
         }
+        _ => return format!("<{} bytes binary>", chunk.length()),
+    };
 
-        write!(f, "{}", png_str)
+    let mut preview: String = text
+        .chars()
+        .take(PREVIEW_LENGTH)
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+    if text.chars().count() > PREVIEW_LENGTH {
+        preview.push_str("...");
     }
+
+    format!("\"{}\"", preview)
 }
 
 #[allow(unused_variables)]
@@ -155,7 +1003,6 @@ mod tests {
     use crate::chunk::Chunk;
     use crate::chunk_type::{ChunkType, ParseChunkTypeError};
     use std::convert::TryFrom;
-    // use std::str::FromStr;
 
     fn testing_chunks() -> Vec<Chunk> {
         vec![
@@ -171,8 +1018,6 @@ mod tests {
     }
 
     fn chunk_from_strings(chunk_type: &str, data: &str) -> Result<Chunk, ParseChunkTypeError> {
-        use std::str::FromStr;
-
         let chunk_type = ChunkType::from_str(chunk_type)?;
         let data: Vec<u8> = data.bytes().collect();
 
@@ -187,6 +1032,63 @@ mod tests {
         assert_eq!(png.chunks().len(), 3);
     }
 
+    #[test]
+    fn test_iter() {
+        let png = testing_png();
+        assert_eq!(png.iter().count(), png.len());
+        assert!(!png.is_empty());
+
+        let mut count = 0;
+        // EFFECT: counts each Chunk borrowed from the png
+        for chunk in &png {
+            assert_eq!(chunk, &png.chunks()[count]);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        let last = png
+            .iter()
+            .filter(|chunk| chunk.chunk_type().to_string() == "LASt")
+            .count();
+        assert_eq!(last, 1);
+        assert!(Png::with_capacity(4).is_empty());
+    }
+
+    #[test]
+    fn test_new_is_empty() {
+        let png = Png::new();
+
+        assert!(png.is_empty());
+        assert_eq!(png.as_bytes(), Png::STANDARD_HEADER.to_vec());
+        assert_eq!(Png::try_from(png.as_bytes()).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_from_chunks_round_trip() {
+        let chunks = vec![
+            chunk_from_strings("OnEe", "first custom chunk").unwrap(),
+            chunk_from_strings("twOo", "second custom chunk").unwrap(),
+        ];
+        let png = Png::from_chunks(chunks.clone());
+
+        let parsed = Png::try_from(png.as_bytes()).unwrap();
+        assert_eq!(parsed.chunks(), chunks.as_slice());
+        assert_eq!(parsed.as_bytes(), png.as_bytes());
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut png = Png::with_capacity(1000);
+
+        // EFFECT: fills the png up to its capacity
+        for _ in 0..1000 {
+            png.append_chunk(chunk_from_strings("RuSt", "Message").unwrap());
+        }
+
+        assert_eq!(png.chunks().len(), 1000);
+        assert!(png.chunks.capacity() >= 1000);
+    }
+
     #[test]
     fn test_valid_from_bytes() {
         let chunk_bytes: Vec<u8> = testing_chunks()
@@ -223,6 +1125,209 @@ mod tests {
         assert!(png.is_err());
     }
 
+    #[test]
+    fn test_non_png_bytes_have_bad_signature() {
+        let text_file = "This is just a text file, not a png".as_bytes();
+        assert_eq!(
+            Png::try_from(text_file).err(),
+            Some(ParsePngError::BadSignature)
+        );
+
+        let too_short = [137, 80, 78];
+        assert_eq!(
+            Png::try_from(&too_short[..]).err(),
+            Some(ParsePngError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_truncated_chunk() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.truncate(bytes.len() - 6);
+
+        // the IEND Chunk is the last 12 bytes of the file
+        assert_eq!(
+            Png::try_from(bytes.as_ref()).err(),
+            Some(ParsePngError::ChunkParse {
+                offset: PNG_FILE.len() - 12,
+                source: ParseChunkError::TooShort
+            })
+        );
+    }
+
+    #[test]
+    fn test_truncated_chunk_reports_offset() {
+        let chunks = testing_chunks();
+        let offset = 8 + chunks[0].byte_len() + chunks[1].byte_len();
+        let mut bytes = Png::from_chunks(chunks).as_bytes();
+        bytes.truncate(offset + 10);
+
+        let err = Png::try_from(bytes.as_ref()).unwrap_err();
+        assert_eq!(
+            err,
+            ParsePngError::ChunkParse {
+                offset,
+                source: ParseChunkError::TooShort
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "failed at offset {} parsing chunk: invalid chunk: not enough bytes",
+                offset
+            )
+        );
+    }
+
+    fn png_with_bad_crc() -> Vec<u8> {
+        let mut bytes = PNG_FILE.to_vec();
+        let last_idx = bytes.len() - 1;
+        bytes[last_idx] ^= 0xff;
+        bytes
+    }
+
+    #[test]
+    fn test_default_options_verify_crc() {
+        assert_eq!(
+            Png::try_from(png_with_bad_crc().as_ref()).err(),
+            Some(ParsePngError::ChunkParse {
+                offset: PNG_FILE.len() - 12,
+                source: ParseChunkError::CrcMismatch {
+                    expected: 0xae426082,
+                    actual: 0xae42607d
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_options_without_crc_verification() {
+        let options = ParseOptions {
+            crc: CrcHandling::Recompute,
+            ..ParseOptions::default()
+        };
+        let png = Png::try_from_with_options(&png_with_bad_crc(), &options).unwrap();
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_lenient_keeps_stored_crc() {
+        let bad_bytes = png_with_bad_crc();
+        let png = Png::try_from_lenient(&bad_bytes).unwrap();
+
+        assert_eq!(png.as_bytes(), bad_bytes);
+        assert!(!png.chunks().last().unwrap().crc_is_valid());
+    }
+
+    #[test]
+    fn test_integrity_problems() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert!(png.integrity_problems().is_empty());
+
+        let png = Png::try_from_lenient(&png_with_bad_crc()).unwrap();
+        let problems = png.integrity_problems();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("chunk 6 'IEND' has crc"));
+
+        let problems = testing_png().integrity_problems();
+        assert_eq!(
+            problems,
+            vec![
+                "PNG file has no IHDR chunk".to_string(),
+                "PNG file has no IEND chunk".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_structure() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.validate_structure(), Ok(()));
+
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_chunk(chunk_from_strings("teXt", "after the end").unwrap());
+        let after_iend = png.len() - 1;
+        assert_eq!(
+            png.validate_structure(),
+            Err(ParsePngError::ChunkAfterIend { index: after_iend })
+        );
+        assert_eq!(
+            png.insert_before_end(chunk_from_strings("TeSt", "Message").unwrap()),
+            Err(ParsePngError::ChunkAfterIend { index: after_iend })
+        );
+    }
+
+    #[test]
+    fn test_validate_structure_misplaced_chunks() {
+        let ihdr = Png::try_from(&PNG_FILE[..]).unwrap().chunks()[0].clone();
+        let iend = chunk_from_strings("IEND", "").unwrap();
+
+        let png = Png::from_chunks(vec![iend.clone(), ihdr.clone(), iend.clone()]);
+        assert_eq!(
+            png.validate_structure(),
+            Err(ParsePngError::IhdrNotFirst { index: 1 })
+        );
+        assert_eq!(
+            png.integrity_problems(),
+            vec![
+                "PNG file has its IHDR chunk at index 1 instead of first".to_string(),
+                "PNG file has a chunk after IEND at index 1".to_string(),
+                "PNG file has 2 IEND chunks, expected 1".to_string()
+            ]
+        );
+
+        let png = Png::from_chunks(vec![ihdr, iend.clone(), iend]);
+        assert_eq!(
+            png.validate_structure(),
+            Err(ParsePngError::RepeatedChunk {
+                chunk_type: ChunkType::from_str("IEND").unwrap(),
+                count: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_options_with_limits() {
+        let few_chunks = ParseOptions {
+            max_chunks: Some(2),
+            ..ParseOptions::default()
+        };
+        let chunks = Png::try_from(&PNG_FILE[..]).unwrap().chunks().to_vec();
+        let offset_of = |index: usize| {
+            Png::STANDARD_HEADER.len()
+                + chunks[..index]
+                    .iter()
+                    .map(|chunk| chunk.as_bytes().len())
+                    .sum::<usize>()
+        };
+        assert_eq!(
+            Png::try_from_with_options(&PNG_FILE, &few_chunks).err(),
+            Some(ParsePngError::TooManyChunks {
+                offset: offset_of(2)
+            })
+        );
+
+        let short_chunks = ParseOptions {
+            crc: CrcHandling::Recompute,
+            max_chunk_data_len: Some(16),
+            ..ParseOptions::default()
+        };
+        let first_long = chunks.iter().position(|chunk| chunk.length() > 16).unwrap();
+        assert_eq!(
+            Png::try_from_with_options(&png_with_bad_crc(), &short_chunks).err(),
+            Some(ParsePngError::ChunkTooLong {
+                offset: offset_of(first_long)
+            })
+        );
+
+        let roomy = ParseOptions {
+            max_chunks: Some(10),
+            max_chunk_data_len: Some(8192),
+            ..ParseOptions::default()
+        };
+        assert!(Png::try_from_with_options(&PNG_FILE, &roomy).is_ok());
+    }
+
     #[test]
     fn test_invalid_chunk() {
         let mut chunk_bytes: Vec<u8> = testing_chunks()
@@ -260,6 +1365,27 @@ mod tests {
         assert_eq!(&chunk.data_as_string().unwrap(), "I am the first chunk");
     }
 
+    #[test]
+    fn test_chunk_types() {
+        let png = Png::try_from(crate::testutil::minimal_png()).unwrap();
+
+        assert_eq!(png.chunk_types(), ["IHDR", "IDAT", "IEND"]);
+        assert!(png.contains_chunk_type("IDAT"));
+        assert!(!png.contains_chunk_type("ruSt"));
+        assert!(Png::new().chunk_types().is_empty());
+    }
+
+    #[test]
+    fn test_last_chunk_by_type() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("FrSt", "I am the newest chunk").unwrap());
+
+        let chunk = png.last_chunk_by_type("FrSt").unwrap();
+        assert_eq!(&chunk.data_as_string().unwrap(), "I am the newest chunk");
+        assert_eq!(png.last_chunk_by_type("miDl"), png.chunk_by_type("miDl"));
+        assert!(png.last_chunk_by_type("noNe").is_none());
+    }
+
     #[test]
     fn test_append_chunk() {
         let mut png = testing_png();
@@ -269,6 +1395,52 @@ mod tests {
         assert_eq!(&chunk.data_as_string().unwrap(), "Message");
     }
 
+    #[test]
+    fn test_insert_before_end() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_before_end(chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+
+        let chunks = png.chunks();
+        assert_eq!(chunks[chunks.len() - 2].chunk_type().to_string(), "TeSt");
+        assert_eq!(chunks[chunks.len() - 1].chunk_type().to_string(), "IEND");
+    }
+
+    #[test]
+    fn test_insert_before_missing_end() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.chunks.pop();
+        let result = png.insert_before_end(chunk_from_strings("TeSt", "Message").unwrap());
+
+        assert_eq!(result, Err(ParsePngError::MissingIend));
+        assert_eq!(png.chunks().len(), 6);
+    }
+
+    #[test]
+    fn test_append_text() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.append_text("ruSt", "A quick message").unwrap();
+
+        let chunks = png.chunks();
+        let chunk = &chunks[chunks.len() - 2];
+        assert_eq!(&chunk.chunk_type().to_string(), "ruSt");
+        assert_eq!(&chunk.data_as_string().unwrap(), "A quick message");
+        assert_eq!(
+            png.append_text("ru", "Too short"),
+            Err(AppendTextError::InvalidChunk(
+                ParseChunkError::InvalidChunkType
+            ))
+        );
+
+        let mut no_iend = Png::from_chunks(png.chunks()[..png.len() - 1].to_vec());
+        assert_eq!(
+            no_iend.append_text("ruSt", "Nowhere to go"),
+            Err(AppendTextError::InvalidStructure(
+                ParsePngError::MissingIend
+            ))
+        );
+    }
+
     #[test]
     fn test_remove_first_chunk() {
         let mut png = testing_png();
@@ -278,12 +1450,448 @@ mod tests {
         assert!(chunk.is_none());
     }
 
+    #[test]
+    fn test_chunk_by_index() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+
+        assert_eq!(
+            png.chunk_by_index(0).unwrap().chunk_type().to_string(),
+            "IHDR"
+        );
+        assert_eq!(png.chunk_by_index(5).unwrap().data(), b"hey");
+        assert!(png.chunk_by_index(png.len()).is_none());
+    }
+
+    #[test]
+    fn test_remove_chunk_at() {
+        let mut png = testing_png();
+        let removed = png.remove_chunk_at(1).unwrap();
+
+        assert_eq!(removed.chunk_type().to_string(), "miDl");
+        assert_eq!(png.len(), 2);
+        assert_eq!(png.chunks()[1].chunk_type().to_string(), "LASt");
+    }
+
+    #[test]
+    fn test_move_chunk_forward() {
+        let mut png = testing_png();
+        png.append_chunk(chunk_from_strings("FoUr", "I am the fourth chunk").unwrap());
+        png.move_chunk(2, 0).unwrap();
+
+        assert_eq!(png.chunk_types(), ["LASt", "FrSt", "miDl", "FoUr"]);
+
+        png.move_chunk(0, 3).unwrap();
+        assert_eq!(png.chunks()[3].chunk_type().to_string(), "LASt");
+        assert_eq!(
+            png.move_chunk(1, 4),
+            Err(ChunkEditError::IndexOutOfRange { index: 4, len: 4 })
+        );
+    }
+
+    #[test]
+    fn test_move_chunk_keeps_ihdr_and_iend_in_place() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let last_idx = png.len() - 1;
+
+        assert!(matches!(
+            png.move_chunk(0, 2),
+            Err(ChunkEditError::Pinned(_))
+        ));
+        assert!(matches!(
+            png.move_chunk(2, 0),
+            Err(ChunkEditError::Pinned(_))
+        ));
+        assert!(matches!(
+            png.move_chunk(last_idx, 1),
+            Err(ChunkEditError::Pinned(_))
+        ));
+        assert!(matches!(
+            png.move_chunk(1, last_idx),
+            Err(ChunkEditError::Pinned(_))
+        ));
+
+        png.move_chunk(5, 1).unwrap();
+        assert_eq!(png.chunks()[1].data(), b"hey");
+        assert!(png.integrity_problems().is_empty());
+    }
+
+    #[test]
+    fn test_insert_chunk_at() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let len = png.len();
+        png.insert_chunk_at(1, chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+
+        let order: Vec<String> = png
+            .iter()
+            .take(3)
+            .map(|chunk| chunk.chunk_type().to_string())
+            .collect();
+        assert_eq!(order, ["IHDR", "TeSt", "sRGB"]);
+        assert_eq!(png.len(), len + 1);
+        assert!(png.integrity_problems().is_empty());
+    }
+
+    #[test]
+    fn test_insert_chunk_at_keeps_ihdr_and_iend_in_place() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let len = png.len();
+        let chunk = chunk_from_strings("TeSt", "Message").unwrap();
+
+        assert!(matches!(
+            png.insert_chunk_at(0, chunk.clone()),
+            Err(ChunkEditError::Pinned(_))
+        ));
+        assert!(matches!(
+            png.insert_chunk_at(len, chunk.clone()),
+            Err(ChunkEditError::Pinned(_))
+        ));
+        assert_eq!(
+            png.insert_chunk_at(len + 1, chunk.clone()),
+            Err(ChunkEditError::IndexOutOfRange {
+                index: len + 1,
+                len
+            })
+        );
+
+        png.insert_chunk_at(len - 1, chunk.clone()).unwrap();
+        assert_eq!(png.chunks()[len - 1].chunk_type().to_string(), "TeSt");
+        assert_eq!(png.chunks()[len].chunk_type().to_string(), "IEND");
+
+        // a Chunk after an IEND that isn't last is still past IEND
+        let mut early_iend = Png::from_chunks(png.chunks()[..2].to_vec());
+        early_iend.append_chunk(png.chunks()[len].clone());
+        early_iend.append_chunk(png.chunks()[1].clone());
+        assert!(matches!(
+            early_iend.insert_chunk_at(3, chunk.clone()),
+            Err(ChunkEditError::Pinned(_))
+        ));
+        assert!(matches!(
+            Png::from_chunks(Vec::new()).insert_chunk_at(0, chunk),
+            Err(ChunkEditError::Pinned(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_chunk_type() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let index = png.len() - 2;
+        let old = png.chunks()[index].clone();
+        let new_type = ChunkType::from_str("teNt").unwrap();
+
+        png.rename_chunk_type(index, new_type.clone()).unwrap();
+        let renamed = &png.chunks()[index];
+        assert_eq!(renamed.chunk_type(), &new_type);
+        assert_eq!(renamed.data(), old.data());
+        assert_ne!(renamed.crc(), old.crc());
+        assert!(renamed.crc_is_valid());
+    }
+
+    #[test]
+    fn test_rename_chunk_type_keeps_ihdr_and_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let ihdr = ChunkType::from_str("IHDR").unwrap();
+        let iend = ChunkType::from_str("IEND").unwrap();
+        let len = png.len();
+
+        assert_eq!(
+            png.rename_chunk_type(0, ChunkType::from_str("teNt").unwrap()),
+            Err(ChunkEditError::Unrenamable(ihdr))
+        );
+        assert_eq!(
+            png.rename_chunk_type(1, iend.clone()),
+            Err(ChunkEditError::Unrenamable(iend))
+        );
+        assert_eq!(
+            png.rename_chunk_type(len, ChunkType::from_str("teNt").unwrap()),
+            Err(ChunkEditError::IndexOutOfRange { index: len, len })
+        );
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_remove_chunk_at_out_of_range() {
+        let mut png = testing_png();
+        assert_eq!(
+            png.remove_chunk_at(3),
+            Err(ChunkEditError::IndexOutOfRange { index: 3, len: 3 })
+        );
+        assert_eq!(png.len(), 3);
+    }
+
+    #[test]
+    fn test_remove_all_chunks_by_type() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_before_end(chunk_from_strings("ruSt", "one").unwrap())
+            .unwrap();
+        png.insert_before_end(chunk_from_strings("ruSt", "two").unwrap())
+            .unwrap();
+        png.insert_before_end(chunk_from_strings("ruSt", "three").unwrap())
+            .unwrap();
+        let len = png.len();
+
+        assert_eq!(png.remove_all_chunks_by_type("ruSt"), Ok(3));
+        assert!(!png.contains_chunk_type("ruSt"));
+        assert_eq!(png.len(), len - 3);
+        assert_eq!(png.remove_all_chunks_by_type("ruSt"), Ok(0));
+        assert!(matches!(
+            png.remove_all_chunks_by_type("IEND"),
+            Err(ChunkEditError::Protected(_))
+        ));
+    }
+
+    #[test]
+    fn test_remove_first_chunk_refuses_ihdr_and_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let len = png.len();
+
+        assert!(matches!(
+            png.remove_first_chunk("IHDR"),
+            Err(ChunkEditError::Protected(_))
+        ));
+        assert!(matches!(
+            png.remove_first_chunk("IEND"),
+            Err(ChunkEditError::Protected(_))
+        ));
+        assert_eq!(png.len(), len);
+    }
+
+    #[test]
+    fn test_remove_chunk_at_refuses_iend() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let last_idx = png.len() - 1;
+
+        assert!(matches!(
+            png.remove_chunk_at(last_idx),
+            Err(ChunkEditError::Protected(_))
+        ));
+        assert!(matches!(
+            png.remove_chunk_at(0),
+            Err(ChunkEditError::Protected(_))
+        ));
+    }
+
+    #[test]
+    fn test_clone_is_independent() {
+        let png = testing_png();
+        let mut cloned = png.clone();
+        cloned.append_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        cloned.remove_first_chunk("FrSt").unwrap();
+
+        assert_eq!(png.chunks().len(), 3);
+        assert!(png.chunk_by_type("FrSt").is_some());
+        assert!(png.chunk_by_type("TeSt").is_none());
+        assert_eq!(cloned.chunks().len(), 3);
+        assert!(cloned.chunk_by_type("FrSt").is_none());
+    }
+
+    #[test]
+    fn test_recompute_crcs() {
+        let mut bytes = PNG_FILE.to_vec();
+        let last_idx = bytes.len() - 1;
+        bytes[last_idx] ^= 0xff;
+        assert!(Png::try_from(bytes.as_slice()).is_err());
+
+        let mut png = Png::try_from_lenient(&bytes).unwrap();
+        assert_eq!(png.recompute_crcs(), 1);
+        assert_eq!(png.recompute_crcs(), 0);
+        let reparsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert_eq!(reparsed.as_bytes(), PNG_FILE);
+    }
+
+    #[test]
+    fn test_retype_ancillary() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let target = ChunkType::from_str("ruSt").unwrap();
+
+        assert_eq!(png.retype_ancillary(&target), 3);
+        for chunk in png.chunks() {
+            if !chunk.chunk_type().is_critical() {
+                assert_eq!(chunk.chunk_type(), &target);
+            }
+        }
+        assert_eq!(png.chunk_by_type("IHDR").unwrap().length(), 13);
+
+        let reparsed = Png::try_from(png.as_bytes().as_ref()).unwrap();
+        assert_eq!(reparsed.as_bytes(), png.as_bytes());
+    }
+
+    #[test]
+    fn test_strip_ancillary() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_before_end(chunk_from_strings("tEXt", "Title\0pngme").unwrap())
+            .unwrap();
+        let mut without_text = png.clone();
+
+        assert_eq!(png.strip_ancillary(true), 3);
+        assert!(png.chunk_by_type("tEXt").is_some());
+        assert_eq!(without_text.strip_ancillary(false), 4);
+        assert!(
+            without_text
+                .iter()
+                .all(|chunk| chunk.chunk_type().is_critical())
+        );
+        assert!(without_text.integrity_problems().is_empty());
+    }
+
+    #[test]
+    fn test_clean_png_has_no_hidden_data_signs() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.remove_first_chunk("RuSt").unwrap();
+
+        assert!(png.hidden_data_signs().is_empty());
+    }
+
+    #[test]
+    fn test_injected_chunk_has_hidden_data_signs() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.remove_first_chunk("RuSt").unwrap();
+        png.insert_before_end(Chunk::new(
+            ChunkType::from_str("ruSt").unwrap(),
+            vec![0; 2048],
+        ))
+        .unwrap();
+        png.append_chunk(chunk_from_strings("teXt", "after the end").unwrap());
+
+        let signs = png.hidden_data_signs();
+        assert_eq!(signs.len(), 4);
+        assert!(signs.contains(&String::from("non-standard chunk 'ruSt' at index 5")));
+        assert!(signs.contains(&String::from("1 chunks after IEND")));
+    }
+
+    #[test]
+    fn test_replace_first_chunk() {
+        let mut png = testing_png();
+        let old_crc = png.chunk_by_type("miDl").unwrap().crc();
+        png.replace_first_chunk("miDl", chunk_from_strings("miDl", "I changed").unwrap())
+            .unwrap();
+
+        let chunk = &png.chunks()[1];
+        assert_eq!(&chunk.chunk_type().to_string(), "miDl");
+        assert_eq!(&chunk.data_as_string().unwrap(), "I changed");
+        assert_ne!(chunk.crc(), old_crc);
+        assert_eq!(png.chunks().len(), 3);
+    }
+
+    #[test]
+    fn test_replace_missing_chunk() {
+        let mut png = testing_png();
+        let result = png.replace_first_chunk("TeSt", chunk_from_strings("TeSt", "New").unwrap());
+
+        assert_eq!(result, Err(ChunkEditError::NotFound));
+        assert_eq!(png.chunks().len(), 3);
+    }
+
     #[test]
     fn test_png_from_image_file() {
         let png = Png::try_from(&PNG_FILE[..]);
         assert!(png.is_ok());
     }
 
+    #[test]
+    fn test_png_from_owned_bytes() {
+        let from_owned = Png::try_from(PNG_FILE.to_vec()).unwrap();
+        let from_slice = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(from_owned.as_bytes(), from_slice.as_bytes());
+    }
+
+    #[test]
+    fn test_compression_ratio() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        assert_eq!(png.raw_image_size(), Some(50 * 50 * 4));
+        assert_eq!(png.idat_size(), 4681);
+
+        let ratio = png.compression_ratio().unwrap();
+        assert!(ratio > 0.0 && ratio < 1.0);
+        assert!((ratio - 0.4681).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compression_ratio_without_ihdr() {
+        assert_eq!(testing_png().compression_ratio(), None);
+    }
+
+    #[test]
+    fn test_total_data_size() {
+        let png = testing_png();
+        assert_eq!(png.total_data_size(), 20 + 18 + 19);
+        assert_eq!(
+            png.as_bytes().len() as u64,
+            8 + 12 * 3 + png.total_data_size()
+        );
+        assert_eq!(Png::with_capacity(0).total_data_size(), 0);
+    }
+
+    #[test]
+    fn test_png_from_reader() {
+        let png = Png::from_reader(std::io::Cursor::new(PNG_FILE.to_vec())).unwrap();
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+    }
+
+    #[test]
+    fn test_png_from_truncated_reader() {
+        let truncated = &PNG_FILE[..PNG_FILE.len() - 12];
+        let err = Png::from_reader(truncated).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParsePngError>(),
+            Some(&ParsePngError::MissingIend)
+        );
+
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let cut = png.chunks()[png.len() - 2].clone();
+        let cut_chunk = &PNG_FILE[..PNG_FILE.len() - 20];
+        let err = Png::from_reader(cut_chunk).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParsePngError>(),
+            Some(&ParsePngError::ChunkParse {
+                offset: PNG_FILE.len() - 12 - cut.byte_len(),
+                source: ParseChunkError::TooShort
+            })
+        );
+
+        let cut_chunk = &PNG_FILE[..PNG_FILE.len() - 14];
+        let err = Png::from_reader(cut_chunk).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParsePngError>(),
+            Some(&ParsePngError::ChunkParse {
+                offset: PNG_FILE.len() - 12 - cut.byte_len(),
+                source: ParseChunkError::LengthMismatch {
+                    declared: cut.length(),
+                    actual: cut.length() - 2
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_png_from_bytes_with_trailing() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend_from_slice(b"PK\x03\x04appended");
+
+        let (png, trailing) = Png::from_bytes_with_trailing(&bytes).unwrap();
+        assert_eq!(png.as_bytes(), PNG_FILE);
+        assert_eq!(trailing, b"PK\x03\x04appended");
+
+        let (_, trailing) = Png::from_bytes_with_trailing(&PNG_FILE[..]).unwrap();
+        assert!(trailing.is_empty());
+    }
+
+    #[test]
+    fn test_png_from_reader_with_huge_length() {
+        let mut bytes = PNG_FILE[..8].to_vec();
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        bytes.extend_from_slice(b"IDAT");
+
+        let err = Png::from_reader(bytes.as_slice()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParsePngError>(),
+            Some(&ParsePngError::ChunkParse {
+                offset: 8,
+                source: ParseChunkError::TooLong { declared: u32::MAX }
+            })
+        );
+    }
+
     #[test]
     fn test_as_bytes() {
         let png = Png::try_from(&PNG_FILE[..]).unwrap();
@@ -292,6 +1900,25 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_write_to_matches_as_bytes() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+        png.write_to(&mut cursor).unwrap();
+
+        assert_eq!(cursor.into_inner(), png.as_bytes());
+    }
+
+    #[test]
+    fn test_byte_len_matches_as_bytes() {
+        let png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let bytes = png.as_bytes();
+
+        assert_eq!(png.byte_len(), PNG_FILE.len());
+        assert_eq!(bytes.len(), png.byte_len());
+        assert_eq!(bytes.capacity(), png.byte_len());
+    }
+
     #[test]
     fn test_png_trait_impls() {
         let chunk_bytes: Vec<u8> = testing_chunks()
@@ -310,6 +1937,22 @@ mod tests {
         let _png_string = format!("{}", png);
     }
 
+    #[test]
+    fn test_png_display_summary() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        png.insert_before_end(chunk_from_strings("loNg", &"ab".repeat(30)).unwrap())
+            .unwrap();
+        let summary = format!("{}", png);
+        let lines: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(lines[0], "Signature: 89 50 4e 47 0d 0a 1a 0a");
+        assert_eq!(lines[1], "0  IHDR  13  <13 bytes binary>");
+        assert_eq!(lines[5], "4  IDAT  4681  <4681 bytes binary>");
+        assert_eq!(lines[6], "5  RuSt  3  \"hey\"");
+        assert_eq!(lines[7], format!("6  loNg  60  \"{}...\"", "ab".repeat(20)));
+        assert_eq!(lines[8], "7  IEND  0  \"\"");
+    }
+
     // // This is the raw bytes for a shrunken version of the `dice.png` image on Wikipedia
     const PNG_FILE: [u8; 4803] = [
         137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 50, 0, 0, 0, 50, 8,
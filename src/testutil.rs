@@ -0,0 +1,48 @@
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Returns the bytes of a valid 1x1 PNG: the signature, then an IHDR Chunk for one 8-bit
+/// grayscale pixel, an IDAT Chunk holding that black pixel, and an IEND Chunk
+pub fn minimal_png() -> Vec<u8> {
+    let mut ihdr = Vec::<u8>::new();
+    ihdr.extend_from_slice(&1u32.to_be_bytes());
+    ihdr.extend_from_slice(&1u32.to_be_bytes());
+    // bit depth 8, grayscale, deflate, adaptive filtering, no interlace
+    ihdr.extend_from_slice(&[8, 0, 0, 0, 0]);
+
+    // a scanline is a filter type byte followed by its pixels
+    let mut encoder = ZlibEncoder::new(Vec::<u8>::new(), Compression::default());
+    encoder.write_all(&[0, 0]).unwrap();
+    let idat = encoder.finish().unwrap();
+
+    let chunks = [
+        Chunk::new(ChunkType::from_str("IHDR").unwrap(), ihdr),
+        Chunk::new(ChunkType::from_str("IDAT").unwrap(), idat),
+        Chunk::new(ChunkType::from_str("IEND").unwrap(), Vec::new()),
+    ];
+    Png::STANDARD_HEADER
+        .into_iter()
+        .chain(chunks.iter().flat_map(|chunk| chunk.as_bytes()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_png_parses() {
+        let png = Png::try_from(minimal_png()).unwrap();
+
+        assert_eq!(png.len(), 3);
+        assert_eq!(png.validate_structure(), Ok(()));
+        assert!(png.integrity_problems().is_empty());
+        assert_eq!(png.raw_image_size(), Some(1));
+        assert_eq!(png.as_bytes(), minimal_png());
+    }
+}
@@ -0,0 +1,169 @@
+use assert_cmd::Command;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Copies the 1x1 fixture PNG into a new temp dir and returns the dir with the copy's path
+fn fixture_copy() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("pixel.png");
+    std::fs::copy("tests/fixtures/pixel.png", &path).unwrap();
+    (dir, path)
+}
+
+/// Builds a pngme command with the given arguments, run against the given file
+fn pngme(args: &[&str], path: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("pngme").unwrap();
+    cmd.arg(args[0]).arg(path).args(&args[1..]);
+    cmd
+}
+
+#[test]
+fn test_encode_decode_remove_round_trip() {
+    let (_dir, path) = fixture_copy();
+
+    pngme(&["encode", "ruSt", "a secret", "--force"], &path)
+        .assert()
+        .success();
+    pngme(&["decode", "ruSt", "--quiet"], &path)
+        .assert()
+        .success()
+        .stdout("a secret\n");
+    pngme(&["remove", "ruSt", "--force"], &path)
+        .assert()
+        .success();
+    pngme(&["decode", "ruSt"], &path)
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Error: no chunk of type 'ruSt' found\n");
+}
+
+#[test]
+fn test_encode_auto_type_without_chunk_type() {
+    let (_dir, path) = fixture_copy();
+    let message_file = path.with_file_name("message.txt");
+    std::fs::write(&message_file, "hi").unwrap();
+    let message_arg = message_file.to_str().unwrap();
+
+    pngme(
+        &[
+            "encode",
+            "--auto-type",
+            "--message-file",
+            message_arg,
+            "--force",
+        ],
+        &path,
+    )
+    .assert()
+    .success()
+    .stdout("Encoding with chunk type ruSt\n");
+    pngme(
+        &[
+            "encode",
+            "--auto-type",
+            "--message-file",
+            message_arg,
+            "--force",
+            "--quiet",
+        ],
+        &path,
+    )
+    .assert()
+    .success()
+    .stdout("");
+    pngme(&["decode", "ruSt", "--quiet"], &path)
+        .assert()
+        .success()
+        .stdout("hi\n");
+}
+
+#[test]
+fn test_encode_auto_type_rejects_positionals() {
+    let (_dir, path) = fixture_copy();
+    let before = std::fs::read(&path).unwrap();
+    let out = path.with_file_name("out.png");
+
+    pngme(
+        &[
+            "encode",
+            "hello",
+            out.to_str().unwrap(),
+            "--auto-type",
+            "--force",
+        ],
+        &path,
+    )
+    .assert()
+    .failure()
+    .code(2);
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+    assert!(!out.exists());
+}
+
+#[test]
+fn test_print_lists_encoded_chunk() {
+    let (_dir, path) = fixture_copy();
+
+    pngme(&["encode", "ruSt", "shown", "--force"], &path)
+        .assert()
+        .success();
+    let output = pngme(&["print"], &path).assert().success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("ruSt"));
+    assert!(stdout.contains("\"shown\""));
+}
+
+#[test]
+fn test_bad_chunk_type_fails() {
+    let (_dir, path) = fixture_copy();
+    let before = std::fs::read(&path).unwrap();
+
+    pngme(&["encode", "ru5t", "a secret", "--force"], &path)
+        .assert()
+        .failure()
+        .code(1);
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+}
+
+#[test]
+fn test_missing_file_fails() {
+    let (dir, _path) = fixture_copy();
+
+    pngme(&["decode", "ruSt"], &dir.path().join("missing.png"))
+        .assert()
+        .failure()
+        .code(1);
+}
+
+#[test]
+fn test_remove_from_stdin_to_output() {
+    let (_dir, path) = fixture_copy();
+    let out = path.with_file_name("out.png");
+    let out_arg = out.to_str().unwrap();
+    pngme(&["encode", "ruSt", "a secret", "--force"], &path)
+        .assert()
+        .success();
+    let encoded = std::fs::read(&path).unwrap();
+
+    Command::cargo_bin("pngme")
+        .unwrap()
+        .args(["remove", "-", "ruSt", "-o", out_arg])
+        .write_stdin(encoded.clone())
+        .assert()
+        .success();
+    assert_eq!(std::fs::read(&path).unwrap(), encoded);
+    assert_eq!(
+        std::fs::read(&out).unwrap(),
+        std::fs::read("tests/fixtures/pixel.png").unwrap()
+    );
+
+    pngme(
+        &["remove", path.to_str().unwrap(), "ruSt", "-o", out_arg],
+        &path,
+    )
+    .assert()
+    .failure()
+    .code(1)
+    .stderr("Error: --output can only be used with a single file\n");
+}